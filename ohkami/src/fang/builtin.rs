@@ -1,6 +1,9 @@
 mod basicauth;
 pub use basicauth::BasicAuth;
 
+mod client_ip;
+pub use client_ip::{ClientIp, ClientIpHeader, TrustProxies};
+
 mod cors;
 pub use cors::CORS;
 
@@ -0,0 +1,394 @@
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use crate::{FromRequest, Request};
+
+
+/// # Client's IP address
+///
+/// Extracted as
+///
+/// - the directly connected peer's address ( `Request::ip` ) by default,
+/// - the address told by trusted proxies when `ClientIp::trust` is fanged
+///   and the peer is one of them.
+///
+/// On `rt_worker`, where no peer address is available, the peer is
+/// regarded as the address in `CF-Connecting-IP` set by Cloudflare.
+///
+/// Extraction fails with `400 Bad Request` when no address is found,
+/// for example, for a connection via Unix domain socket without `ClientIp::trust`.
+/// Use `Option<ClientIp>` to handle it by yourself.
+///
+/// <br>
+///
+/// ```no_run
+/// use ohkami::prelude::*;
+/// use ohkami::fang::ClientIp;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     Ohkami::with(
+///         ClientIp::trust(["10.0.0.0/8", "127.0.0.1"]), // <--
+///         (
+///             "/whoami".GET(whoami),
+///         )
+///     ).howl("0.0.0.0:8080").await
+/// }
+///
+/// async fn whoami(ClientIp(ip): ClientIp) -> String {
+///     format!("You are {ip}")
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+impl<'req> FromRequest<'req> for ClientIp {
+    type Error = std::convert::Infallible;
+
+    #[inline]
+    fn from_request(req: &'req Request) -> Option<Result<Self, Self::Error>> {
+        let peer = peer_of(req);
+        let ip = match req.memorized::<Arc<Trusted>>() {
+            Some(trusted) => trusted.client_ip(req, peer),
+            None          => peer,
+        };
+        ip.map(|ip| Ok(ClientIp(ip)))
+    }
+}
+
+#[cfg(feature="__rt_native__")]
+#[inline]
+fn peer_of(req: &Request) -> Option<IpAddr> {
+    req.ip
+}
+#[cfg(not(feature="__rt_native__"))]
+#[inline]
+fn peer_of(req: &Request) -> Option<IpAddr> {
+    parse_node(header(req, ClientIpHeader::CFConnectingIp)?)
+}
+
+/// Headers telling the client's address, passed to `TrustProxies::headers`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClientIpHeader {
+    /// `Forwarded` ( RFC 7239 ), using the `for` parameters
+    Forwarded,
+    /// `X-Forwarded-For`
+    XForwardedFor,
+    /// `X-Real-IP`
+    XRealIp,
+    /// `CF-Connecting-IP`
+    CFConnectingIp,
+}
+
+impl ClientIpHeader {
+    const fn name(&self) -> &'static str {
+        match self {
+            Self::Forwarded      => "Forwarded",
+            Self::XForwardedFor  => "X-Forwarded-For",
+            Self::XRealIp        => "X-Real-IP",
+            Self::CFConnectingIp => "CF-Connecting-IP",
+        }
+    }
+}
+
+fn header(req: &Request, header: ClientIpHeader) -> Option<&str> {
+    /* custom headers are stored as sent, so look them up case-insensitively */
+    match header {
+        ClientIpHeader::Forwarded => req.headers.Forwarded(),
+        _ => req.headers.iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(header.name()))
+            .map(|(_, value)| value),
+    }
+}
+
+/// `192.0.2.1`, `192.0.2.1:4711`, `2001:db8::1` or `[2001:db8::1]:4711`,
+/// optionally quoted as in `Forwarded`
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+    node.parse::<IpAddr>().ok()
+        .or_else(|| node.parse::<SocketAddr>().ok().map(|a| a.ip()))
+        .or_else(|| node.strip_prefix('[')?.strip_suffix(']')?.parse().ok())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Cidr {
+    addr:   IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    fn parse(cidr: &str) -> Option<Self> {
+        let (addr, prefix) = match cidr.trim().split_once('/') {
+            Some((addr, prefix)) => (addr.parse::<IpAddr>().ok()?, Some(prefix.parse::<u8>().ok()?)),
+            None                 => (cidr.trim().parse::<IpAddr>().ok()?, None),
+        };
+        let max = if addr.is_ipv4() {32} else {128};
+        let prefix = prefix.unwrap_or(max);
+        (prefix <= max).then_some(Self { addr, prefix })
+    }
+
+    fn contains(&self, ip: IpAddr) -> bool {
+        /* `::ffff:10.0.0.1` from a dual-stack listener is `10.0.0.1` */
+        let ip = match ip {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+            IpAddr::V4(_)  => ip,
+        };
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Trusted {
+    proxies: Vec<Cidr>,
+    headers: Vec<ClientIpHeader>,
+}
+
+impl Trusted {
+    fn trusts(&self, ip: IpAddr) -> bool {
+        self.proxies.iter().any(|cidr| cidr.contains(ip))
+    }
+
+    /// The address told by the first present one of `headers` if `peer` is trusted.
+    ///
+    /// For the lists in `Forwarded` or `X-Forwarded-For`, each of which
+    /// is appended by each proxy, this takes the rightmost untrusted one
+    /// as addresses on the left of it may be forged by the client.
+    fn client_ip(&self, req: &Request, peer: Option<IpAddr>) -> Option<IpAddr> {
+        /* a peer via Unix domain socket is a local process, typically a reverse proxy */
+        if peer.is_some_and(|ip| !self.trusts(ip)) {
+            return peer
+        }
+
+        let Some((header, value)) = self.headers.iter()
+            .find_map(|&h| header(req, h).map(|value| (h, value)))
+        else {return peer};
+
+        let mut nodes: Box<dyn Iterator<Item = Option<IpAddr>>> = match header {
+            ClientIpHeader::Forwarded => Box::new(value.split(',').rev().map(|element| {
+                element.split(';').find_map(|pair| {
+                    let (key, value) = pair.split_once('=')?;
+                    key.trim().eq_ignore_ascii_case("for").then_some(value)
+                }).and_then(parse_node)
+            })),
+            ClientIpHeader::XForwardedFor => Box::new(value.split(',').rev().map(parse_node)),
+            ClientIpHeader::XRealIp | ClientIpHeader::CFConnectingIp => Box::new([parse_node(value)].into_iter()),
+        };
+
+        let mut client = peer;
+        /* stop at an unknown or obfuscated node, taking the last proxy known */
+        while let Some(Some(ip)) = nodes.next() {
+            client = Some(ip);
+            if !self.trusts(ip) {break}
+        }
+        client
+    }
+}
+
+impl ClientIp {
+    /// Trust `proxies`, each of which is an IP address like `127.0.0.1`
+    /// or a CIDR block like `10.0.0.0/8`, `fd00::/8`.
+    ///
+    /// By default, the first present one of `Forwarded`, `X-Forwarded-For`,
+    /// `X-Real-IP` and `CF-Connecting-IP` is used. Restrict it by `.headers(...)`
+    /// to the ones your proxies actually set, otherwise a client can send
+    /// the others by itself.
+    ///
+    /// ## Panics
+    ///
+    /// When any of `proxies` is not a valid IP address or CIDR block.
+    pub fn trust(proxies: impl IntoIterator<Item = impl AsRef<str>>) -> TrustProxies {
+        let proxies = proxies.into_iter().map(|proxy| {
+            let proxy = proxy.as_ref();
+            Cidr::parse(proxy).unwrap_or_else(|| panic!("`ClientIp::trust`: invalid IP address or CIDR block: `{proxy}`"))
+        }).collect();
+        TrustProxies(Trusted {
+            proxies,
+            headers: vec![
+                ClientIpHeader::Forwarded,
+                ClientIpHeader::XForwardedFor,
+                ClientIpHeader::XRealIp,
+                ClientIpHeader::CFConnectingIp,
+            ],
+        })
+    }
+}
+
+/// Fang configuring `ClientIp`, created by `ClientIp::trust`
+#[derive(Clone)]
+pub struct TrustProxies(Trusted);
+
+impl TrustProxies {
+    /// Use only `headers`, in this order of priority
+    pub fn headers(mut self, headers: impl IntoIterator<Item = ClientIpHeader>) -> Self {
+        self.0.headers = headers.into_iter().collect();
+        self
+    }
+}
+
+const _: () = {
+    use crate::fang::{Fang, FangProc};
+
+    impl<Inner: FangProc> Fang<Inner> for TrustProxies {
+        type Proc = TrustProxiesProc<Inner>;
+        fn chain(&self, inner: Inner) -> Self::Proc {
+            TrustProxiesProc { trusted: Arc::new(self.0.clone()), inner }
+        }
+    }
+
+    pub struct TrustProxiesProc<Inner: FangProc> {
+        trusted: Arc<Trusted>,
+        inner:   Inner,
+    }
+    impl<Inner: FangProc> FangProc for TrustProxiesProc<Inner> {
+        #[cfg(not(feature="rt_worker"))]
+        #[inline]
+        fn bite<'b>(&'b self, req: &'b mut Request) -> impl std::future::Future<Output = crate::Response> + Send {
+            req.memorize(self.trusted.clone());
+            self.inner.bite(req)
+        }
+        #[cfg(feature="rt_worker")]
+        #[inline]
+        fn bite<'b>(&'b self, req: &'b mut Request) -> impl std::future::Future<Output = crate::Response> {
+            req.memorize(self.trusted.clone());
+            self.inner.bite(req)
+        }
+    }
+};
+
+
+
+
+#[cfg(any(feature="rt_tokio",feature="rt_async-std"))]
+#[cfg(feature="testing")]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::*;
+    use crate::testing::*;
+
+    #[test] fn test_cidr() {
+        let cidr = Cidr::parse("10.0.0.0/8").unwrap();
+        assert!(cidr.contains("10.1.2.3".parse().unwrap()));
+        assert!(cidr.contains("::ffff:10.1.2.3".parse().unwrap()));
+        assert!(!cidr.contains("11.0.0.1".parse().unwrap()));
+
+        let cidr = Cidr::parse("fd00::/8").unwrap();
+        assert!(cidr.contains("fd12::1".parse().unwrap()));
+        assert!(!cidr.contains("fe80::1".parse().unwrap()));
+
+        assert!(Cidr::parse("0.0.0.0/0").unwrap().contains("192.0.2.1".parse().unwrap()));
+        assert!(Cidr::parse("127.0.0.1").unwrap().contains("127.0.0.1".parse().unwrap()));
+        assert!(!Cidr::parse("127.0.0.1").unwrap().contains("127.0.0.2".parse().unwrap()));
+
+        assert_eq!(Cidr::parse("10.0.0.0/33"), None);
+        assert_eq!(Cidr::parse("localhost"), None);
+    }
+
+    #[test] fn test_parse_node() {
+        assert_eq!(parse_node(" 192.0.2.1"), Some("192.0.2.1".parse().unwrap()));
+        assert_eq!(parse_node("192.0.2.1:4711"), Some("192.0.2.1".parse().unwrap()));
+        assert_eq!(parse_node("\"[2001:db8::1]:4711\""), Some("2001:db8::1".parse().unwrap()));
+        assert_eq!(parse_node("[2001:db8::1]"), Some("2001:db8::1".parse().unwrap()));
+        assert_eq!(parse_node("unknown"), None);
+        assert_eq!(parse_node("_hidden"), None);
+    }
+
+    async fn whoami(ClientIp(ip): ClientIp) -> String {
+        ip.to_string()
+    }
+
+    #[crate::__rt__::test] async fn client_ip() {
+        /* `TestRequest`s come from 0.0.0.0 */
+
+        let t = Ohkami::new((
+            "/".GET(whoami),
+        )).test(); {
+            let req = TestRequest::GET("/")
+                .header("X-Forwarded-For", "192.0.2.1");
+            let res = t.oneshot(req).await;
+            assert_eq!(res.text(), Some("0.0.0.0"));
+        }
+
+        let t = Ohkami::with(ClientIp::trust(["10.0.0.0/8"]), (
+            "/".GET(whoami),
+        )).test(); {
+            let req = TestRequest::GET("/")
+                .header("X-Forwarded-For", "192.0.2.1");
+            let res = t.oneshot(req).await;
+            assert_eq!(res.text(), Some("0.0.0.0"));
+        }
+
+        let t = Ohkami::with(ClientIp::trust(["0.0.0.0", "10.0.0.0/8"]), (
+            "/".GET(whoami),
+        )).test(); {
+            let req = TestRequest::GET("/");
+            let res = t.oneshot(req).await;
+            assert_eq!(res.text(), Some("0.0.0.0"));
+        } {
+            let req = TestRequest::GET("/")
+                .header("X-Forwarded-For", "198.51.100.7, 192.0.2.1, 10.0.0.2");
+            let res = t.oneshot(req).await;
+            assert_eq!(res.text(), Some("192.0.2.1"));
+        } {
+            let req = TestRequest::GET("/")
+                .header("X-Forwarded-For", "10.0.0.3, 10.0.0.2");
+            let res = t.oneshot(req).await;
+            assert_eq!(res.text(), Some("10.0.0.3"));
+        } {
+            let req = TestRequest::GET("/")
+                .header("Forwarded", "for=192.0.2.43, for=\"[2001:db8:cafe::17]:4711\";proto=https, for=10.0.0.2")
+                .header("X-Forwarded-For", "198.51.100.7");
+            let res = t.oneshot(req).await;
+            assert_eq!(res.text(), Some("2001:db8:cafe::17"));
+        } {
+            let req = TestRequest::GET("/")
+                .header("Forwarded", "for=192.0.2.43, for=unknown, for=10.0.0.2");
+            let res = t.oneshot(req).await;
+            assert_eq!(res.text(), Some("10.0.0.2"));
+        } {
+            let req = TestRequest::GET("/")
+                .header("X-Real-IP", "192.0.2.1");
+            let res = t.oneshot(req).await;
+            assert_eq!(res.text(), Some("192.0.2.1"));
+        } {
+            let req = TestRequest::GET("/")
+                .header("cf-connecting-ip", "192.0.2.1");
+            let res = t.oneshot(req).await;
+            assert_eq!(res.text(), Some("192.0.2.1"));
+        } {
+            let req = TestRequest::GET("/")
+                .header("x-Forwarded-For", "198.51.100.7, 10.0.0.2");
+            let res = t.oneshot(req).await;
+            assert_eq!(res.text(), Some("198.51.100.7"));
+        } {
+            let req = TestRequest::GET("/")
+                .header("X-Real-Ip", "192.0.2.1");
+            let res = t.oneshot(req).await;
+            assert_eq!(res.text(), Some("192.0.2.1"));
+        }
+
+        let t = Ohkami::with(ClientIp::trust(["0.0.0.0"]).headers([ClientIpHeader::XRealIp]), (
+            "/".GET(whoami),
+        )).test(); {
+            let req = TestRequest::GET("/")
+                .header("X-Forwarded-For", "198.51.100.7")
+                .header("X-Real-IP", "192.0.2.1");
+            let res = t.oneshot(req).await;
+            assert_eq!(res.text(), Some("192.0.2.1"));
+        } {
+            let req = TestRequest::GET("/")
+                .header("X-Forwarded-For", "198.51.100.7");
+            let res = t.oneshot(req).await;
+            assert_eq!(res.text(), Some("0.0.0.0"));
+        }
+    }
+}
@@ -22,6 +22,25 @@ pub trait IntoHandler<T> {
         .map_err(IntoResponse::into_response)
 }
 
+#[inline(always)] fn from_param<'p, P: FromParam<'p>>(
+    req:   &Request,
+    index: usize,
+) -> Result<P, Response> {
+    // SAFETY: `req.path` is alive while the handler, the same as `from_request`
+    match unsafe {req.path.nth_param(index)} {
+        Some(raw_param) => P::from_raw_param(raw_param),
+        None => Err(missing_param(req, index)),
+    }
+}
+
+#[cold] #[inline(never)] fn missing_param(req: &Request, index: usize) -> Response {
+    crate::warning!(
+        "[WARNING] A handler for `{}` takes {} or more path params, but the route has {}",
+        req.path, index + 1, req.path.params().count()
+    );
+    Response::InternalServerError()
+}
+
 
 const _: (/* no args */) = {
     impl<'req, F, Body, Fut> IntoHandler<fn()->Body> for F
@@ -50,7 +69,7 @@ const _: (/* FromParam */) = {
     {
         fn into_handler(self) -> Handler {
             Handler::new(move |req|
-                match P1::from_raw_param(unsafe {req.path.assume_one_param()}) {
+                match from_param::<P1>(req, 0) {
                     Ok(p1) => {
                         let res = self(p1);
                         Box::pin(async move {res.await.into_response()})
@@ -69,9 +88,7 @@ const _: (/* FromParam */) = {
     {
         fn into_handler(self) -> Handler {
             Handler::new(move |req|
-                // SAFETY: Due to the architecture of `Router`,
-                // `params` has already `append`ed once before this code
-                match P1::from_raw_param(unsafe {req.path.assume_one_param()}) {
+                match from_param::<P1>(req, 0) {
                     Ok(p1) => {
                         let res = self((p1,));
                         Box::pin(async move {res.await.into_response()})
@@ -81,25 +98,6 @@ const _: (/* FromParam */) = {
             )
         }
     }
-
-    impl<'req, F, Fut, Body:IntoResponse, P1:FromParam<'req>, P2:FromParam<'req>> IntoHandler<fn(((P1, P2),))->Body> for F
-    where
-        F:   Fn((P1, P2)) -> Fut + SendSyncOnNative + 'static,
-        Fut: Future<Output = Body> + SendOnNative + 'static,
-    {
-        fn into_handler(self) -> Handler {
-            Handler::new(move |req| {
-                let (p1, p2) = unsafe {req.path.assume_two_params()};
-                match (P1::from_raw_param(p1), P2::from_raw_param(p2)) {
-                    (Ok(p1), Ok(p2)) => {
-                        let res = self((p1, p2));
-                        Box::pin(async move {res.await.into_response()})
-                    }
-                    (Err(e), _) | (_, Err(e)) => __error__(e),
-                }
-            })
-        }
-    }
 };
 
 const _: (/* FromRequest items */) = {
@@ -118,6 +116,7 @@ const _: (/* FromRequest items */) = {
                     Err(e) => __error__(e)
                 }
             )
+            .streaming(Item1::STREAMS_PAYLOAD)
         }
     }
 
@@ -137,6 +136,7 @@ const _: (/* FromRequest items */) = {
                     (_, Err(e)) => __error__(e),
                 }
             )
+            .streaming(Item1::STREAMS_PAYLOAD || Item2::STREAMS_PAYLOAD)
         }
     }
 
@@ -157,6 +157,7 @@ const _: (/* FromRequest items */) = {
                     (_, _, Err(e)) => __error__(e),
                 }
             )
+            .streaming(Item1::STREAMS_PAYLOAD || Item2::STREAMS_PAYLOAD || Item3::STREAMS_PAYLOAD)
         }
     }
 
@@ -178,6 +179,7 @@ const _: (/* FromRequest items */) = {
                     (_,_, _, Err(e)) => __error__(e),
                 }
             )
+            .streaming(Item1::STREAMS_PAYLOAD || Item2::STREAMS_PAYLOAD || Item3::STREAMS_PAYLOAD || Item4::STREAMS_PAYLOAD)
         }
     }
 };
@@ -190,11 +192,7 @@ const _: (/* one FromParam without tuple and FromRequest items */) = {
     {
         fn into_handler(self) -> Handler {
             Handler::new(move |req| {
-                // SAFETY: Due to the architecture of `Router`,
-                // `params` has already `append`ed once before this code
-                let p1 = unsafe {req.path.assume_one_param()};
-
-                match (P1::from_raw_param(p1), from_request(req)) {
+                match (from_param::<P1>(req, 0), from_request(req)) {
                     (Ok(p1), Ok(item1)) => {
                         let res = self(p1, item1);
                         Box::pin(async move {res.await.into_response()})
@@ -203,6 +201,7 @@ const _: (/* one FromParam without tuple and FromRequest items */) = {
                     (_, Err(e)) => __error__(e),
                 }
             })
+            .streaming(Item1::STREAMS_PAYLOAD)
         }
     }
 
@@ -213,11 +212,7 @@ const _: (/* one FromParam without tuple and FromRequest items */) = {
     {
         fn into_handler(self) -> Handler {
             Handler::new(move |req| {
-                // SAFETY: Due to the architecture of `Router`,
-                // `params` has already `append`ed once before this code
-                let p1 = unsafe {req.path.assume_one_param()};
-
-                match (P1::from_raw_param(p1), from_request::<Item1>(req), from_request::<Item2>(req)) {
+                match (from_param::<P1>(req, 0), from_request::<Item1>(req), from_request::<Item2>(req)) {
                     (Ok(p1), Ok(item1), Ok(item2)) => {
                         let res = self(p1, item1, item2);
                         Box::pin(async move {res.await.into_response()})
@@ -227,6 +222,7 @@ const _: (/* one FromParam without tuple and FromRequest items */) = {
                     (_,_,Err(e)) => __error__(e),
                 }
             })
+            .streaming(Item1::STREAMS_PAYLOAD || Item2::STREAMS_PAYLOAD)
         }
     }
 
@@ -237,11 +233,7 @@ const _: (/* one FromParam without tuple and FromRequest items */) = {
     {
         fn into_handler(self) -> Handler {
             Handler::new(move |req| {
-                // SAFETY: Due to the architecture of `Router`,
-                // `params` has already `append`ed once before this code
-                let p1 = unsafe {req.path.assume_one_param()};
-
-                match (P1::from_raw_param(p1), from_request::<Item1>(req), from_request::<Item2>(req), from_request::<Item3>(req)) {
+                match (from_param::<P1>(req, 0), from_request::<Item1>(req), from_request::<Item2>(req), from_request::<Item3>(req)) {
                     (Ok(p1), Ok(item1), Ok(item2), Ok(item3)) => {
                         let res = self(p1, item1, item2, item3);
                         Box::pin(async move {res.await.into_response()})
@@ -252,6 +244,7 @@ const _: (/* one FromParam without tuple and FromRequest items */) = {
                     (_,_,_,Err(e)) => __error__(e),
                 }
             })
+            .streaming(Item1::STREAMS_PAYLOAD || Item2::STREAMS_PAYLOAD || Item3::STREAMS_PAYLOAD)
         }
     }
 
@@ -262,11 +255,7 @@ const _: (/* one FromParam without tuple and FromRequest items */) = {
     {
         fn into_handler(self) -> Handler {
             Handler::new(move |req| {
-                // SAFETY: Due to the architecture of `Router`,
-                // `params` has already `append`ed once before this code
-                let p1 = unsafe {req.path.assume_one_param()};
-
-                match (P1::from_raw_param(p1), from_request::<Item1>(req), from_request::<Item2>(req), from_request::<Item3>(req), from_request::<Item4>(req)) {
+                match (from_param::<P1>(req, 0), from_request::<Item1>(req), from_request::<Item2>(req), from_request::<Item3>(req), from_request::<Item4>(req)) {
                     (Ok(p1), Ok(item1), Ok(item2), Ok(item3), Ok(item4)) => {
                         let res = self(p1, item1, item2, item3, item4);
                         Box::pin(async move {res.await.into_response()})
@@ -278,6 +267,7 @@ const _: (/* one FromParam without tuple and FromRequest items */) = {
                     (_,_,_,_,Err(e)) => __error__(e),
                 }
             })
+            .streaming(Item1::STREAMS_PAYLOAD || Item2::STREAMS_PAYLOAD || Item3::STREAMS_PAYLOAD || Item4::STREAMS_PAYLOAD)
         }
     }
 };
@@ -290,11 +280,7 @@ const _: (/* one FromParam and FromRequest items */) = {
     {
         fn into_handler(self) -> Handler {
             Handler::new(move |req| {
-                // SAFETY: Due to the architecture of `Router`,
-                // `params` has already `append`ed once before this code
-                let p1 = unsafe {req.path.assume_one_param()};
-
-                match (P1::from_raw_param(p1), from_request::<Item1>(req)) {
+                match (from_param::<P1>(req, 0), from_request::<Item1>(req)) {
                     (Ok(p1), Ok(item1)) => {
                         let res = self((p1,), item1);
                         Box::pin(async move {res.await.into_response()})
@@ -303,6 +289,7 @@ const _: (/* one FromParam and FromRequest items */) = {
                     (_,Err(e)) => __error__(e),
                 }
             })
+            .streaming(Item1::STREAMS_PAYLOAD)
         }
     }
 
@@ -313,11 +300,7 @@ const _: (/* one FromParam and FromRequest items */) = {
     {
         fn into_handler(self) -> Handler {
             Handler::new(move |req| {
-                // SAFETY: Due to the architecture of `Router`,
-                // `params` has already `append`ed once before this code
-                let p1 = unsafe {req.path.assume_one_param()};
-
-                match (P1::from_raw_param(p1), from_request::<Item1>(req), from_request::<Item2>(req)) {
+                match (from_param::<P1>(req, 0), from_request::<Item1>(req), from_request::<Item2>(req)) {
                     (Ok(p1), Ok(item1), Ok(item2)) => {
                         let res = self((p1,), item1, item2);
                         Box::pin(async move {res.await.into_response()})
@@ -327,6 +310,7 @@ const _: (/* one FromParam and FromRequest items */) = {
                     (_,_,Err(e)) => __error__(e),
                 }
             })
+            .streaming(Item1::STREAMS_PAYLOAD || Item2::STREAMS_PAYLOAD)
         }
     }
 
@@ -337,11 +321,7 @@ const _: (/* one FromParam and FromRequest items */) = {
     {
         fn into_handler(self) -> Handler {
             Handler::new(move |req| {
-                // SAFETY: Due to the architecture of `Router`,
-                // `params` has already `append`ed once before this code
-                let p1 = unsafe {req.path.assume_one_param()};
-                
-                match (P1::from_raw_param(p1), from_request::<Item1>(req), from_request::<Item2>(req), from_request::<Item3>(req)) {
+                match (from_param::<P1>(req, 0), from_request::<Item1>(req), from_request::<Item2>(req), from_request::<Item3>(req)) {
                     (Ok(p1), Ok(item1), Ok(item2), Ok(item3)) => {
                         let res = self((p1,), item1, item2, item3);
                         Box::pin(async move {res.await.into_response()})
@@ -352,6 +332,7 @@ const _: (/* one FromParam and FromRequest items */) = {
                     (_,_,_,Err(e)) => __error__(e),
                 }
             })
+            .streaming(Item1::STREAMS_PAYLOAD || Item2::STREAMS_PAYLOAD || Item3::STREAMS_PAYLOAD)
         }
     }
 
@@ -362,11 +343,7 @@ const _: (/* one FromParam and FromRequest items */) = {
     {
         fn into_handler(self) -> Handler {
             Handler::new(move |req| {
-                // SAFETY: Due to the architecture of `Router`,
-                // `params` has already `append`ed once before this code
-                let p1 = unsafe {req.path.assume_one_param()};
-                
-                match (P1::from_raw_param(p1), from_request::<Item1>(req), from_request::<Item2>(req), from_request::<Item3>(req), from_request::<Item4>(req)) {
+                match (from_param::<P1>(req, 0), from_request::<Item1>(req), from_request::<Item2>(req), from_request::<Item3>(req), from_request::<Item4>(req)) {
                     (Ok(p1), Ok(item1), Ok(item2), Ok(item3), Ok(item4)) => {
                         let res = self((p1,), item1, item2, item3, item4);
                         Box::pin(async move {res.await.into_response()})
@@ -378,111 +355,72 @@ const _: (/* one FromParam and FromRequest items */) = {
                     (_,_,_,_,Err(e)) => __error__(e),
                 }
             })
+            .streaming(Item1::STREAMS_PAYLOAD || Item2::STREAMS_PAYLOAD || Item3::STREAMS_PAYLOAD || Item4::STREAMS_PAYLOAD)
         }
     }
 };
 
-const _: (/* two PathParams and FromRequest items */) = {
-    impl<'req, F, Fut, Body:IntoResponse, P1:FromParam<'req>, P2:FromParam<'req>, Item1:FromRequest<'req>> IntoHandler<fn((P1, P2), Item1)->Body> for F
-    where
-        F:   Fn((P1, P2), Item1) -> Fut + SendSyncOnNative + 'static,
-        Fut: Future<Output = Body> + SendOnNative + 'static,
-    {
-        fn into_handler(self) -> Handler {
-            Handler::new(move |req| {
-                // SAFETY: Due to the architecture of `Router`,
-                // `params` has already `append`ed twice before this code
-                let (p1, p2) = unsafe {req.path.assume_two_params()};
-
-                match (FromParam::from_raw_param(p1), FromParam::from_raw_param(p2), from_request::<Item1>(req)) {
-                    (Ok(p1), Ok(p2), Ok(item1)) => {
-                        let res = self((p1, p2), item1); 
-                        Box::pin(async move {res.await.into_response()})
-                    }
-                    (Err(e),_,_) |
-                    (_,Err(e),_) |
-                    (_,_,Err(e)) => __error__(e),
+const _: (/* tuple of FromParams and FromRequest items */) = {
+    macro_rules! with_params {
+        ( $( ( $( $p:ident $index:tt ),+ ) )* ) => {$(
+            impl<'req, F, Fut, Body:IntoResponse, $($p:FromParam<'req>),+> IntoHandler<fn((($($p,)+),))->Body> for F
+            where
+                F:   Fn(($($p,)+)) -> Fut + SendSyncOnNative + 'static,
+                Fut: Future<Output = Body> + SendOnNative + 'static,
+            {
+                fn into_handler(self) -> Handler {
+                    Handler::new(move |req| {
+                        match (|| Ok::<_, Response>(( $(from_param::<$p>(req, $index)?,)+ )))() {
+                            Ok(params) => {
+                                let res = self(params);
+                                Box::pin(async move {res.await.into_response()})
+                            }
+                            Err(e) => __error__(e),
+                        }
+                    })
                 }
-            })
-        }
-    }
-
-    impl<'req, F, Fut, Body:IntoResponse, P1:FromParam<'req>, P2:FromParam<'req>, Item1:FromRequest<'req>, Item2:FromRequest<'req>> IntoHandler<fn((P1, P2), Item1, Item2)->Body> for F
-    where
-        F:   Fn((P1, P2), Item1, Item2) -> Fut + SendSyncOnNative + 'static,
-        Fut: Future<Output = Body> + SendOnNative + 'static,
-    {
-        fn into_handler(self) -> Handler {
-            Handler::new(move |req| {
-                // SAFETY: Due to the architecture of `Router`,
-                // `params` has already `append`ed twice before this code
-                let (p1, p2) = unsafe {req.path.assume_two_params()};
-
-                match (FromParam::from_raw_param(p1), FromParam::from_raw_param(p2), from_request::<Item1>(req), from_request::<Item2>(req)) {
-                    (Ok(p1), Ok(p2), Ok(item1), Ok(item2)) => {
-                        let res = self((p1, p2), item1, item2);
-                        Box::pin(async move {res.await.into_response()})
-                    }
-                    (Err(e),_,_,_) |
-                    (_,Err(e),_,_) |
-                    (_,_,Err(e),_) |
-                    (_,_,_,Err(e)) => __error__(e),
+            }
+
+            with_params!(@items ( $($p $index),+ ) Item1);
+            with_params!(@items ( $($p $index),+ ) Item1 Item2);
+            with_params!(@items ( $($p $index),+ ) Item1 Item2 Item3);
+            with_params!(@items ( $($p $index),+ ) Item1 Item2 Item3 Item4);
+        )*};
+
+        (@items ( $( $p:ident $index:tt ),+ ) $( $item:ident )+) => {
+            impl<'req, F, Fut, Body:IntoResponse, $($p:FromParam<'req>,)+ $($item:FromRequest<'req>),+> IntoHandler<fn(($($p,)+), $($item),+)->Body> for F
+            where
+                F:   Fn(($($p,)+), $($item),+) -> Fut + SendSyncOnNative + 'static,
+                Fut: Future<Output = Body> + SendOnNative + 'static,
+            {
+                #[allow(non_snake_case)]
+                fn into_handler(self) -> Handler {
+                    Handler::new(move |req| {
+                        match (|| Ok::<_, Response>((
+                            ( $(from_param::<$p>(req, $index)?,)+ ),
+                            $( from_request::<$item>(req)?, )+
+                        )))() {
+                            Ok((params, $($item,)+)) => {
+                                let res = self(params, $($item),+);
+                                Box::pin(async move {res.await.into_response()})
+                            }
+                            Err(e) => __error__(e),
+                        }
+                    })
+                    .streaming(false $(|| $item::STREAMS_PAYLOAD)+)
                 }
-            })
-        }
-    }
-
-    impl<'req, F, Fut, Body:IntoResponse, P1:FromParam<'req>, P2:FromParam<'req>, Item1:FromRequest<'req>, Item2:FromRequest<'req>, Item3:FromRequest<'req>> IntoHandler<fn((P1, P2), Item1, Item2, Item3)->Body> for F
-    where
-        F:   Fn((P1, P2), Item1, Item2, Item3) -> Fut + SendSyncOnNative + 'static,
-        Fut: Future<Output = Body> + SendOnNative + 'static,
-    {
-        fn into_handler(self) -> Handler {
-            Handler::new(move |req| {
-                // SAFETY: Due to the architecture of `Router`,
-                // `params` has already `append`ed twice before this code
-                let (p1, p2) = unsafe {req.path.assume_two_params()};
-
-                match (FromParam::from_raw_param(p1), FromParam::from_raw_param(p2), from_request::<Item1>(req), from_request::<Item2>(req), from_request::<Item3>(req)) {
-                    (Ok(p1), Ok(p2), Ok(item1), Ok(item2), Ok(item3)) => {
-                        let res = self((p1, p2), item1, item2, item3);
-                        Box::pin(async move {res.await.into_response()})
-                    }
-                    (Err(e),_,_,_,_) |
-                    (_,Err(e),_,_,_) |
-                    (_,_,Err(e),_,_) |
-                    (_,_,_,Err(e),_) |
-                    (_,_,_,_,Err(e)) => __error__(e),
-                }
-            })
-        }
+            }
+        };
     }
 
-    impl<'req, F, Fut, Body:IntoResponse, P1:FromParam<'req>, P2:FromParam<'req>, Item1:FromRequest<'req>, Item2:FromRequest<'req>, Item3:FromRequest<'req>, Item4:FromRequest<'req>> IntoHandler<fn((P1, P2), Item1, Item2, Item3, Item4)->Body> for F
-    where
-        F:   Fn((P1, P2), Item1, Item2, Item3, Item4) -> Fut + SendSyncOnNative + 'static,
-        Fut: Future<Output = Body> + SendOnNative + 'static,
-    {
-        fn into_handler(self) -> Handler {
-            Handler::new(move |req| {
-                // SAFETY: Due to the architecture of `Router`,
-                // `params` has already `append`ed twice before this code
-                let (p1, p2) = unsafe {req.path.assume_two_params()};
-
-                match (FromParam::from_raw_param(p1), FromParam::from_raw_param(p2), from_request::<Item1>(req), from_request::<Item2>(req), from_request::<Item3>(req), from_request::<Item4>(req)) {
-                    (Ok(p1), Ok(p2), Ok(item1), Ok(item2), Ok(item3), Ok(item4)) => {
-                        let res = self((p1, p2), item1, item2, item3, item4);
-                        Box::pin(async move {res.await.into_response()})
-                    }
-                    (Err(e),_,_,_,_,_) |
-                    (_,Err(e),_,_,_,_) |
-                    (_,_,Err(e),_,_,_) |
-                    (_,_,_,Err(e),_,_) |
-                    (_,_,_,_,Err(e),_) |
-                    (_,_,_,_,_,Err(e)) => __error__(e),
-                }
-            })
-        }
+    with_params! {
+        (P1 0, P2 1)
+        (P1 0, P2 1, P3 2)
+        (P1 0, P2 1, P3 2, P4 3)
+        (P1 0, P2 1, P3 2, P4 3, P5 4)
+        (P1 0, P2 1, P3 2, P4 3, P5 4, P6 5)
+        (P1 0, P2 1, P3 2, P4 3, P5 4, P6 5, P7 6)
+        (P1 0, P2 1, P3 2, P4 3, P5 4, P6 5, P7 6, P8 7)
     }
 };
 
@@ -502,6 +440,8 @@ const _: (/* two PathParams and FromRequest items */) = {
     }
     async fn h3(_param: P) -> String {format!("")}
 
+    async fn h5(_params: (P, String, &str, P, u8, u16, u32, u64), _req: &Request) -> String {String::new()}
+
     #[cfg(feature="rt_worker")]
     struct SomeJS {_ptr: *const u8}
     #[cfg(feature="rt_worker")]
@@ -520,7 +460,7 @@ const _: (/* two PathParams and FromRequest items */) = {
         };
     }
 
-    assert_handlers! { h0 h1 h2 h3  }
+    assert_handlers! { h0 h1 h2 h3 h5 }
 
     #[cfg(feature="rt_worker")]
     assert_handlers! { h4 }
@@ -8,12 +8,43 @@ use std::{pin::Pin, future::Future};
 
 
 #[derive(Clone)]
-pub struct Handler(BoxedFPC);
+pub struct Handler {
+    proc: BoxedFPC,
+    /// whether this takes the request body as a stream ( or never uses it ),
+    /// and so the body must not be buffered before this
+    pub(crate) streams_payload: bool,
+}
 
 const _: () = {
     impl Into<BoxedFPC> for Handler {
         fn into(self) -> BoxedFPC {
-            self.0
+            #[cfg(feature="__rt_native__")]
+            if !self.streams_payload {
+                return BoxedFPC::from_proc(BufferPayload(self.proc))
+            }
+            self.proc
+        }
+    }
+
+    /// Buffers the payload left unread until the handler,
+    /// e.g. for a request with `Expect: 100-continue` that fangs may reject
+    /// before its body is sent
+    #[cfg(feature="__rt_native__")]
+    struct BufferPayload(BoxedFPC);
+
+    #[cfg(feature="__rt_native__")]
+    impl FangProcCaller for BufferPayload {
+        fn call_bite<'b>(&'b self, req: &'b mut Request) -> Pin<Box<dyn Future<Output = Response> + Send + 'b>> {
+            if req.has_unread_payload() {
+                Box::pin(async move {
+                    match req.load_payload().await {
+                        Ok(()) => self.0.call_bite(req).await,
+                        Err(res) => res,
+                    }
+                })
+            } else {
+                self.0.call_bite(req)
+            }
         }
     }
 
@@ -50,7 +81,15 @@ impl Handler {
             }
         };
 
-        Self(BoxedFPC::from_proc(HandlerProc(proc)))
+        Self {
+            proc: BoxedFPC::from_proc(HandlerProc(proc)),
+            streams_payload: false,
+        }
+    }
+
+    pub(crate) fn streaming(mut self, streams_payload: bool) -> Self {
+        self.streams_payload = streams_payload;
+        self
     }
 }
 
@@ -68,9 +107,10 @@ impl Handler {
             async fn not_found() -> Response {
                 Response::NotFound()
             }
-            not_found.into_handler()
+            /* not to read the body of a request to nowhere */
+            not_found.into_handler().streaming(true)
         });
 
-        Handler((&*NOT_FOUND).0.clone())
+        (&*NOT_FOUND).clone()
     }
 }
@@ -62,9 +62,33 @@ use crate::{Request, Response};
 ///     }
 /// }
 /// ```
+/// 
+/// <br>
+/// 
+/// ---
+/// *limit_expected_upload.rs*
+/// ```
+/// use ohkami::prelude::*;
+/// 
+/// #[derive(Clone)]
+/// struct LimitUpload(usize);
+/// impl FangAction for LimitUpload {
+///     async fn fore<'a>(&'a self, req: &'a mut Request) -> Result<(), Response> {
+///         /* rejected before `100 Continue` if the client expects it */
+///         match req.headers.ContentLength().and_then(|len| len.parse::<usize>().ok()) {
+///             Some(len) if len > self.0 => Err(Response::PayloadTooLarge()),
+///             _ => Ok(())
+///         }
+///     }
+/// }
+/// ```
 pub trait FangAction: Clone + Send + Sync + 'static {
     /// *fore fang*, that bites a request before a handler.
     /// 
+    /// For a request with `Expect: 100-continue`, this is called before
+    /// `100 Continue` is sent and so `req.payload()` is `None` here.
+    /// Returning `Err` rejects the request without receiving its body.
+    /// 
     /// **Default**: just return `Ok(())`
     #[allow(unused_variables)]
     fn fore<'a>(&'a self, req: &'a mut Request) -> impl std::future::Future<Output = Result<(), Response>> + Send {
@@ -16,6 +16,13 @@ pub use html::HTML;
 mod query;
 pub use query::Query;
 
+mod path;
+pub use path::Path;
+
+mod stream;
+#[cfg(feature="__rt_native__")]
+pub use stream::BodyStream;
+
 
 #[cold] #[inline(never)]
 fn reject(msg: impl std::fmt::Display) -> crate::Response {
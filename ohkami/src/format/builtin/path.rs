@@ -0,0 +1,123 @@
+use crate::{FromRequest, Request, Response};
+use serde::Deserialize;
+use serde::de::{Error as _, IntoDeserializer, Visitor, value::{Error, MapDeserializer}};
+use ohkami_lib::percent_decode_utf8;
+use std::borrow::Cow;
+
+
+/// Path params mapped onto fields by their names in the route
+///
+/// ```
+/// use ohkami::prelude::*;
+/// use ohkami::format::Path;
+///
+/// #[derive(Deserialize)]
+/// struct PostParams<'req> {
+///     user_id: &'req str,
+///     post_id: u64,
+/// }
+///
+/// async fn get_post(
+///     Path(params): Path<PostParams<'_>>,
+/// ) -> String {
+///     format!("post {} of {}", params.post_id, params.user_id)
+/// }
+///
+/// # let _ =
+/// Ohkami::new((
+///     "/users/:user_id/posts/:post_id"
+///         .GET(get_post),
+/// ))
+/// # ;
+/// ```
+pub struct Path<Schema>(pub Schema);
+
+impl<'req, S: Deserialize<'req>> FromRequest<'req> for Path<S> {
+    type Error = Response;
+
+    #[inline]
+    fn from_request(req: &'req Request) -> Option<Result<Self, Self::Error>> {
+        let params = req.path.raw_params()
+            .map(|(name, raw)| (name, Param(raw)));
+        S::deserialize(MapDeserializer::<_, Error>::new(params))
+            .map_err(super::reject)
+            .map(Self).into()
+    }
+}
+
+/// A path param, percent-decoded and parsed on demand
+struct Param<'de>(&'de [u8]);
+
+impl<'de> Param<'de> {
+    fn decoded(&self) -> Result<Cow<'de, str>, Error> {
+        percent_decode_utf8(self.0)
+            .map_err(|_| Error::custom(format!("Non UTF-8 path param `{}`", self.0.escape_ascii())))
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Param<'de> {
+    type Deserializer = Self;
+    fn into_deserializer(self) -> Self::Deserializer {self}
+}
+
+macro_rules! deserialize_parsed {
+    ($( $deserialize:ident => $visit:ident ),* $(,)?) => {$(
+        fn $deserialize<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+            let param = self.decoded()?;
+            visitor.$visit(param.parse().map_err(|e| Error::custom(
+                format!("Unexpected path param `{param}`: {e}")
+            ))?)
+        }
+    )*};
+}
+
+impl<'de> serde::Deserializer<'de> for Param<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.decoded()? {
+            Cow::Borrowed(s) => visitor.visit_borrowed_str(s),
+            Cow::Owned(s)    => visitor.visit_string(s),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_enum(self.decoded()?.into_deserializer())
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool,
+        deserialize_i8   => visit_i8,
+        deserialize_i16  => visit_i16,
+        deserialize_i32  => visit_i32,
+        deserialize_i64  => visit_i64,
+        deserialize_u8   => visit_u8,
+        deserialize_u16  => visit_u16,
+        deserialize_u32  => visit_u32,
+        deserialize_u64  => visit_u64,
+        deserialize_f32  => visit_f32,
+        deserialize_f64  => visit_f64,
+    }
+
+    serde::forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct
+        seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
@@ -0,0 +1,115 @@
+#![cfg(feature="__rt_native__")]
+
+use crate::{FromRequest, Request, Response};
+use crate::request::{BodyState, Body};
+use ohkami_lib::stream::Stream;
+use std::{future::Future, pin::Pin, sync::Mutex};
+use std::task::{Context, Poll};
+
+
+/// # Request body as a stream of chunks
+///
+/// When a handler takes `BodyStream`, the request body is **not** buffered
+/// before the handler and read from the connection chunk by chunk as polled,
+/// so that a large upload can be piped to somewhere without holding it in memory.
+///
+/// Each chunk is read within `body_read_timeout` of `ServerConfig`,
+/// and the whole body is limited by `max_payload_size`.
+///
+/// <br>
+///
+/// ---
+/// *example.rs*
+/// ```no_run
+/// use ohkami::prelude::*;
+/// use ohkami::format::BodyStream;
+/// use ohkami::util::StreamExt;
+///
+/// async fn upload(mut body: BodyStream<'_>) -> Result<String, Response> {
+///     let mut size = 0;
+///     while let Some(chunk) = body.next().await {
+///         let chunk = chunk?;
+///         size += chunk.len();
+///     }
+///     Ok(format!("received {size} bytes"))
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     Ohkami::new((
+///         "/upload".POST(upload),
+///     )).howl("localhost:5050").await
+/// }
+/// ```
+/// ---
+///
+/// <br>
+///
+/// _**note**_ : If the body is left unread when the handler returns,
+/// the connection is closed after the response.
+pub struct BodyStream<'req> {
+    slot:  &'req Mutex<BodyState>,
+    state: State,
+}
+
+enum State {
+    Buffered(Option<Vec<u8>>),
+    Unread(Option<Body>),
+    Reading(ReadChunk),
+}
+
+/// Reading the next chunk, giving the `Body` back with it
+type ReadChunk = Pin<Box<dyn Future<Output = (Body, Option<Result<Vec<u8>, Response>>)> + Send>>;
+
+impl<'req> FromRequest<'req> for BodyStream<'req> {
+    type Error = std::convert::Infallible;
+
+    const STREAMS_PAYLOAD: bool = true;
+
+    fn from_request(req: &'req Request) -> Option<Result<Self, Self::Error>> {
+        let mut slot = req.__body__.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        let state = match std::mem::replace(&mut *slot, BodyState::Streaming) {
+            BodyState::Unread(body) => State::Unread(Some(body)),
+            /* already buffered or taken */
+            other => {
+                *slot = other;
+                State::Buffered(req.payload().map(<[u8]>::to_vec))
+            }
+        };
+        Some(Ok(Self { slot: &req.__body__, state }))
+    }
+}
+
+impl Stream for BodyStream<'_> {
+    type Item = Result<Vec<u8>, Response>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            match &mut this.state {
+                State::Buffered(chunk) => return Poll::Ready(chunk.take().map(Ok)),
+                State::Unread(body) => {
+                    let Some(mut body) = body.take() else {return Poll::Ready(None)};
+                    this.state = State::Reading(Box::pin(async move {
+                        let chunk = body.next().await;
+                        (body, chunk)
+                    }));
+                }
+                State::Reading(reading) => {
+                    let (body, chunk) = ohkami_lib::stream::ready!(reading.as_mut().poll(cx));
+                    this.state = State::Unread(Some(body));
+                    return Poll::Ready(chunk)
+                }
+            }
+        }
+    }
+}
+
+impl Drop for BodyStream<'_> {
+    fn drop(&mut self) {
+        /* a body being read is dropped, leaving `BodyState::Streaming` */
+        if let State::Unread(Some(body)) = std::mem::replace(&mut self.state, State::Buffered(None)) {
+            *self.slot.lock().unwrap_or_else(std::sync::PoisonError::into_inner) = BodyState::Unread(body);
+        }
+    }
+}
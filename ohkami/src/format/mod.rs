@@ -3,11 +3,13 @@
 //! ## Builtin
 //! 
 //! - `Query` - query parameters
+//! - `Path` - path params by their names
 //! - `JSON` - payload of application/json
 //! - `Multipart` - payload of multipart/form-data
 //! - `URLEncoded` - payload of application/x-www-form-urlencoded
 //! - `Text` - payload of text/plain
 //! - `HTML` - payload of text/html
+//! - `BodyStream` - payload as a stream of chunks ( native runtimes )
 
 mod builtin;
 pub use builtin::*;
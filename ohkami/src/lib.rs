@@ -67,6 +67,15 @@ mod __rt__ {
     #[cfg(feature="rt_glommio")]
     pub(crate) use {glommio::net::{TcpListener, TcpStream}, std::net::ToSocketAddrs};
 
+    #[cfg(all(unix, feature="rt_tokio"))]
+    pub(crate) use tokio::net::{UnixListener, UnixStream};
+    #[cfg(all(unix, feature="rt_async-std"))]
+    pub(crate) use async_std::os::unix::net::{UnixListener, UnixStream};
+    #[cfg(all(unix, feature="rt_smol"))]
+    pub(crate) use smol::net::unix::{UnixListener, UnixStream};
+    #[cfg(all(unix, feature="rt_glommio"))]
+    pub(crate) use glommio::net::{UnixListener, UnixStream};
+
     #[cfg(feature="rt_tokio")]
     pub(crate) use tokio::task::spawn;
     #[cfg(feature="rt_async-std")]
@@ -87,6 +96,11 @@ mod __rt__ {
     #[cfg(feature="rt_glommio")]
     pub(crate) use glommio::timer::sleep;
 
+    #[cfg(feature="rt_tokio")]
+    pub(crate) use tokio::io::AsyncRead;
+    #[cfg(any(feature="rt_async-std", feature="rt_smol", feature="rt_glommio"))]
+    pub(crate) use futures_util::AsyncRead;
+
     #[cfg(feature="rt_tokio")]
     pub(crate) use tokio::io::AsyncReadExt as AsyncReader;
     #[cfg(feature="rt_async-std")]
@@ -96,6 +110,11 @@ mod __rt__ {
     #[cfg(feature="rt_glommio")]
     pub(crate) use futures_util::AsyncReadExt as AsyncReader;
 
+    #[cfg(feature="rt_tokio")]
+    pub(crate) use tokio::io::AsyncWrite;
+    #[cfg(any(feature="rt_async-std", feature="rt_smol", feature="rt_glommio"))]
+    pub(crate) use futures_util::AsyncWrite;
+
     #[cfg(feature="rt_tokio")]
     pub(crate) use tokio::io::AsyncWriteExt as AsyncWriter;
     #[cfg(feature="rt_async-std")]
@@ -117,7 +136,7 @@ mod __rt__ {
 
 
 mod request;
-pub use request::{Request, Method, FromRequest, FromParam};
+pub use request::{Request, Method, ExtensionMethod, FromRequest, FromParam};
 pub use ::ohkami_macros::FromRequest;
 
 mod response;
@@ -132,11 +151,17 @@ pub mod format;
 mod session;
 #[cfg(feature="__rt_native__")]
 use session::Session;
+#[cfg(feature="__rt_native__")]
+pub use session::{ProxyHeader, ProxyTls};
 
 #[cfg(feature="__rt__")]
 mod ohkami;
 #[cfg(feature="__rt__")]
-pub use ohkami::{Ohkami, Route};
+pub use ohkami::{Ohkami, Route, PanicInfo};
+#[cfg(feature="__rt_native__")]
+pub use ohkami::{ServerConfig, Shutdown, Listener, ListenerInfo, LocalAddr, Addresses, ConnectionError, ConnectionPhase, OnConnectionError};
+#[cfg(all(feature="tls", feature="__rt_native__"))]
+pub use ohkami::TlsConfig;
 
 pub mod header;
 
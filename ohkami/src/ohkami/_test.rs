@@ -432,6 +432,560 @@ fn duplcate_routes_registration() {
     ));
 }
 
+#[__rt__::test]
+async fn many_path_params() {
+    async fn get_issue(
+        (org, repo, number): (&str, &str, u32),
+    ) -> String {
+        format!("{org}/{repo}#{number}")
+    }
+
+    async fn get_comment(
+        (org, repo, number, id, n): (&str, &str, u32, u64, String),
+        req: &Request,
+    ) -> String {
+        format!("{org}/{repo}#{number} comment {id}/{n} {}", req.method)
+    }
+
+    async fn all_params(req: &Request) -> String {
+        req.path.params().collect::<Vec<_>>().join(",")
+    }
+
+    async fn more_than_route(
+        (a, b, c): (String, String, String),
+    ) -> String {
+        format!("{a}{b}{c}")
+    }
+
+    let t = Ohkami::new((
+        "/orgs/:org/repos/:repo".By(Ohkami::new((
+            "/issues/:number"
+                .GET(get_issue),
+            "/issues/:number/comments/:id/:n"
+                .GET(get_comment),
+        ))),
+        "/:a/:b/:c/:d/:e/:f/:g/:h/:i"
+            .GET(all_params),
+        "/broken/:a/:b"
+            .GET(more_than_route),
+    )).test();
+
+    let req = TestRequest::GET("/orgs/ohkami-rs/repos/ohkami/issues/42");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.status(), Status::OK);
+    assert_eq!(res.text(), Some("ohkami-rs/ohkami#42"));
+
+    let req = TestRequest::GET("/orgs/ohkami-rs/repos/ohkami/issues/42/comments/12345/first%20one");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.status(), Status::OK);
+    assert_eq!(res.text(), Some("ohkami-rs/ohkami#42 comment 12345/first one GET"));
+
+    let req = TestRequest::GET("/orgs/ohkami-rs/repos/ohkami/issues/not-a-number");
+    let res = t.oneshot(req).await;
+    assert!(res.text().unwrap().starts_with("Unexpected path params"));
+
+    let req = TestRequest::GET("/1/2/3/4/5/6/7/8/9");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.text(), Some("1,2,3,4,5,6,7,8,9"));
+
+    let req = TestRequest::GET("/broken/1/2");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.status(), Status::InternalServerError);
+    assert_eq!(res.text(), None);
+
+    /* params of the previous request are not left */
+    let req = TestRequest::GET("/orgs/o/repos/r/issues/1");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.text(), Some("o/r#1"));
+}
+
+#[__rt__::test]
+async fn wildcard_routes() {
+    async fn get_file(path: &str) -> String {
+        format!("file {path}")
+    }
+    async fn get_readme() -> &'static str {
+        "README"
+    }
+    async fn get_user(id: u32) -> String {
+        format!("user {id}")
+    }
+    async fn get_in_user((id, rest): (u32, &str)) -> String {
+        format!("user {id}: {rest}")
+    }
+    async fn spa(path: &str) -> String {
+        format!("spa {path}")
+    }
+
+    let t = Ohkami::new((
+        "/files/*path"
+            .GET(get_file),
+        "/files/README"
+            .GET(get_readme),
+        "/users/:id"
+            .GET(get_user),
+        "/users/:id/*rest"
+            .GET(get_in_user),
+        "/api/health"
+            .GET(|| async {"OK"}),
+        "/*path"
+            .GET(spa),
+    )).test();
+
+    let req = TestRequest::GET("/files/a/b/c.txt");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.status(), Status::OK);
+    assert_eq!(res.text(), Some("file a/b/c.txt"));
+
+    let req = TestRequest::GET("/files/README");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.text(), Some("README"));
+
+    let req = TestRequest::GET("/files/README/old");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.text(), Some("file README/old"));
+
+    let req = TestRequest::GET("/users/42");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.text(), Some("user 42"));
+
+    let req = TestRequest::GET("/users/42/posts/7");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.text(), Some("user 42: posts/7"));
+
+    let req = TestRequest::GET("/api/health");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.text(), Some("OK"));
+
+    /* falling back to the root wildcard */
+    let req = TestRequest::GET("/api/unknown");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.status(), Status::OK);
+    assert_eq!(res.text(), Some("spa api/unknown"));
+
+    let req = TestRequest::GET("/files");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.text(), Some("spa files"));
+
+    let req = TestRequest::GET("/");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.status(), Status::NotFound);
+
+    let t = Ohkami::new((
+        "/files/*path"
+            .GET(get_file),
+    )).test();
+
+    let req = TestRequest::GET("/files");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.status(), Status::NotFound);
+
+    let req = TestRequest::GET("/files/");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.status(), Status::NotFound);
+}
+
+#[__rt__::test]
+async fn static_sections_as_whole_segments() {
+    let t = Ohkami::new((
+        "/users/me"
+            .GET(|| async {"me"}),
+        "/users/:name"
+            .GET(|name: String| async move {format!("user {name}")}),
+        "/api".By(Ohkami::new((
+            "/health".GET(|| async {"OK"}),
+        )).fallback(|| async {Response::NotFound().with_text("no API")})),
+    )).test();
+
+    let req = TestRequest::GET("/users/me");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.text(), Some("me"));
+
+    let req = TestRequest::GET("/users/meow");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.text(), Some("user meow"));
+
+    let req = TestRequest::GET("/api/unknown");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.text(), Some("no API"));
+
+    /* not under `/api` */
+    let req = TestRequest::GET("/apix");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.status(), Status::NotFound);
+    assert_eq!(res.text(), None);
+}
+
+#[__rt__::test]
+async fn named_path_params() {
+    use crate::format::Path;
+
+    #[derive(serde::Deserialize)]
+    struct CommentParams<'req> {
+        org:     &'req str,
+        number:  u32,
+        comment: u64,
+    }
+    async fn get_comment(
+        Path(params): Path<CommentParams<'_>>,
+    ) -> String {
+        format!("{}#{} comment {}", params.org, params.number, params.comment)
+    }
+
+    #[derive(serde::Deserialize)]
+    struct FileParams {
+        user: String,
+        path: String,
+    }
+    async fn get_file(
+        Path(FileParams { user, path }): Path<FileParams>,
+    ) -> String {
+        format!("{path} of {user}")
+    }
+
+    async fn get_by_name(req: &Request) -> String {
+        format!("{:?} {:?}", req.path.param("org"), req.path.param("repo"))
+    }
+
+    let t = Ohkami::new((
+        "/orgs/:org".By(Ohkami::new((
+            "/issues/:number/comments/:comment"
+                .GET(get_comment),
+            "/members"
+                .GET(get_by_name),
+        ))),
+        "/users/:user/files/*path"
+            .GET(get_file),
+    )).test();
+
+    let req = TestRequest::GET("/orgs/ohkami-rs/issues/42/comments/12345");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.status(), Status::OK);
+    assert_eq!(res.text(), Some("ohkami-rs#42 comment 12345"));
+
+    let req = TestRequest::GET("/orgs/ohkami-rs/issues/42/comments/first");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.status(), Status::BadRequest);
+    assert_eq!(res.text(), Some("Unexpected path param `first`: invalid digit found in string"));
+
+    let req = TestRequest::GET("/orgs/ohkami%20rs/members");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.text(), Some(r#"Some("ohkami rs") None"#));
+
+    let req = TestRequest::GET("/users/kanarus/files/a/b%20c.txt");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.text(), Some("a/b c.txt of kanarus"));
+}
+
+#[__rt__::test]
+async fn constrained_path_params() {
+    async fn get_me() -> &'static str {
+        "me"
+    }
+    async fn get_user_by_id(id: u64) -> String {
+        format!("user #{id}")
+    }
+    async fn get_user_by_name(name: &str) -> String {
+        format!("user @{name}")
+    }
+    async fn get_commit(sha: &str) -> String {
+        format!("commit {sha}")
+    }
+    async fn get_post((id, n): (u64, u8)) -> String {
+        format!("post {n} of #{id}")
+    }
+
+    let t = Ohkami::new((
+        "/users/me"
+            .GET(get_me),
+        "/users/:id<u64>"
+            .GET(get_user_by_id),
+        "/users/:name<[a-z-]+>"
+            .GET(get_user_by_name),
+        "/users/:id<u64>/posts/:n<u8>"
+            .GET(get_post),
+        "/commits/:sha<[0-9a-f]{7,40}>"
+            .GET(get_commit),
+    )).test();
+
+    let req = TestRequest::GET("/users/me");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.text(), Some("me"));
+
+    let req = TestRequest::GET("/users/42");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.text(), Some("user #42"));
+
+    let req = TestRequest::GET("/users/ohkami-rs");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.text(), Some("user @ohkami-rs"));
+
+    /* matching none of the constraints, not reaching `FromParam` */
+    let req = TestRequest::GET("/users/Ohkami");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.status(), Status::NotFound);
+
+    let req = TestRequest::GET("/users/99999999999999999999");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.status(), Status::NotFound);
+
+    let req = TestRequest::GET("/users/+42");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.status(), Status::NotFound);
+
+    let req = TestRequest::GET("/users/42/posts/3");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.text(), Some("post 3 of #42"));
+
+    let req = TestRequest::GET("/users/42/posts/300");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.status(), Status::NotFound);
+
+    let req = TestRequest::GET("/commits/0a1b2c3");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.text(), Some("commit 0a1b2c3"));
+
+    let req = TestRequest::GET("/commits/0a1b2c");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.status(), Status::NotFound);
+
+    let req = TestRequest::GET("/commits/0a1b2cz");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.status(), Status::NotFound);
+
+    /* an unconstrained param and a wildcard after constrained ones */
+    let t = Ohkami::new((
+        "/items/:id<u32>"
+            .GET(|id: u32| async move {format!("item #{id}")}),
+        "/items/:key"
+            .GET(|key: String| async move {format!("item {key}")}),
+        "/*path"
+            .GET(|path: String| async move {format!("spa {path}")}),
+    )).test();
+
+    let req = TestRequest::GET("/items/7");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.text(), Some("item #7"));
+
+    let req = TestRequest::GET("/items/seven");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.text(), Some("item seven"));
+
+    let req = TestRequest::GET("/items/7/details");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.text(), Some("spa items/7/details"));
+
+    /* backtracking to the next param when the rest doesn't match under a constrained one */
+    let t = Ohkami::new((
+        "/users/:id<u64>/posts"
+            .GET(|id: u64| async move {format!("posts of #{id}")}),
+        "/users/:name/profile"
+            .GET(|name: String| async move {format!("profile of {name}")}),
+    )).test();
+
+    let req = TestRequest::GET("/users/42/posts");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.text(), Some("posts of #42"));
+
+    let req = TestRequest::GET("/users/42/profile");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.text(), Some("profile of 42"));
+
+    let req = TestRequest::GET("/users/ohkami/profile");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.text(), Some("profile of ohkami"));
+
+    let req = TestRequest::GET("/users/ohkami/posts");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.status(), Status::NotFound);
+}
+
+#[test]
+#[should_panic(expected = "Unknown constraint `<int>`: expected an integer type or `[...]`: `/users/:id<int>`")]
+fn unknown_param_constraint() {
+    Ohkami::new((
+        "/users/:id<int>".GET(|| async {"user"}),
+    ));
+}
+
+#[test]
+#[should_panic(expected = "Invalid repetition `{3,1}` in constraint `<[a-z]{3,1}>`: `/tags/:tag<[a-z]{3,1}>`")]
+fn invalid_param_constraint() {
+    Ohkami::new((
+        "/tags/:tag<[a-z]{3,1}>".GET(|| async {"tag"}),
+    ));
+}
+
+#[__rt__::test]
+async fn fallback_handlers() {
+    #[derive(Clone)]
+    struct Layer(&'static str);
+    impl FangAction for Layer {
+        async fn back<'a>(&'a self, res: &'a mut Response) {
+            let layers = match res.headers.custom("X-Layers") {
+                Some(inner) => format!("{inner},{}", self.0),
+                None        => self.0.to_string(),
+            };
+            res.headers.set().custom("X-Layers", layers);
+        }
+    }
+
+    #[derive(serde::Serialize)]
+    struct ErrorBody {
+        message: String,
+    }
+    async fn api_not_found(req: &Request) -> Response {
+        Response::NotFound().with_json(ErrorBody {
+            message: format!("no API for `{} {}` ({} params)", req.method, req.path.str(), req.path.params().count()),
+        })
+    }
+    async fn not_found_page() -> Response {
+        Response::NotFound().with_html("<h1>Page not found</h1>")
+    }
+
+    let t = Ohkami::with(Layer("root"), (
+        "/".GET(|| async {"top"}),
+        "/api".By(Ohkami::with(Layer("api"), (
+            "/users/:id".GET(|id: u32| async move {format!("user {id}")}),
+            "/v1".By(Ohkami::new((
+                "/health".GET(|| async {"OK"}),
+            ))),
+        )).fallback(api_not_found)),
+    )).fallback(not_found_page).test();
+
+    let req = TestRequest::GET("/api/users/42");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.text(), Some("user 42"));
+    assert_eq!(res.header("X-Layers"), Some("api,root"));
+
+    let req = TestRequest::GET("/api/unknown");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.status(), Status::NotFound);
+    assert_eq!(res.text(), None);
+    assert_eq!(res.json::<serde_json::Value>().unwrap().unwrap(), serde_json::json!({
+        "message": "no API for `GET /api/unknown` (0 params)"
+    }));
+    assert_eq!(res.header("X-Layers"), Some("api,root"));
+
+    /* without params matched halfway */
+    let req = TestRequest::GET("/api/users/42/posts");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.json::<serde_json::Value>().unwrap().unwrap(), serde_json::json!({
+        "message": "no API for `GET /api/users/42/posts` (0 params)"
+    }));
+
+    /* inherited by the nested one without `fallback` */
+    let req = TestRequest::POST("/api/v1/health");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.status(), Status::NotFound);
+    assert!(res.json::<serde_json::Value>().is_some());
+
+    let req = TestRequest::GET("/unknown");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.status(), Status::NotFound);
+    assert_eq!(res.html(), Some("<h1>Page not found</h1>"));
+    assert_eq!(res.header("X-Layers"), Some("root"));
+
+    /* not under `/api` */
+    let req = TestRequest::GET("/apix");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.html(), Some("<h1>Page not found</h1>"));
+    assert_eq!(res.header("X-Layers"), Some("root"));
+
+    /* nested one with a single route */
+    let t = Ohkami::new((
+        "/".GET(|| async {"top"}),
+        "/admin".By(Ohkami::new((
+            "/health".GET(|| async {"OK"}),
+        )).fallback(|| async {Response::NotFound().with_text("no admin page")})),
+    )).test();
+
+    let req = TestRequest::GET("/admin/health");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.text(), Some("OK"));
+
+    let req = TestRequest::GET("/admin/unknown");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.status(), Status::NotFound);
+    assert_eq!(res.text(), Some("no admin page"));
+
+    /* `Ohkami` without `fallback` */
+    let t = Ohkami::new((
+        "/".GET(|| async {"top"}),
+    )).test();
+
+    let req = TestRequest::GET("/unknown");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.status(), Status::NotFound);
+    assert_eq!(res.text(), None);
+}
+
+#[test]
+#[should_panic(expected = "Conflicting fallback registering")]
+fn conflicting_fallbacks() {
+    async fn not_found() -> Response {
+        Response::NotFound()
+    }
+
+    Ohkami::new((
+        "/".By(Ohkami::new((
+            "/a".GET(|| async {"a"}),
+        )).fallback(not_found)),
+    )).fallback(not_found);
+}
+
+#[__rt__::test]
+async fn differently_named_params() {
+    use crate::format::Path;
+
+    #[derive(serde::Deserialize)]
+    struct UserParams<'req> {
+        id: &'req str,
+    }
+    async fn get_user(
+        Path(UserParams { id }): Path<UserParams<'_>>,
+    ) -> String {
+        format!("user {id}")
+    }
+
+    #[derive(serde::Deserialize)]
+    struct PostsParams<'req> {
+        user_id: &'req str,
+    }
+    async fn get_posts(
+        Path(PostsParams { user_id }): Path<PostsParams<'_>>,
+    ) -> String {
+        format!("posts of {user_id}")
+    }
+
+    let t = Ohkami::new((
+        "/users/:id".GET(get_user),
+        "/users/:user_id/posts".GET(get_posts),
+    )).test();
+
+    let req = TestRequest::GET("/users/42");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.status(), Status::OK);
+    assert_eq!(res.text(), Some("user 42"));
+
+    let req = TestRequest::GET("/users/42/posts");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.status(), Status::OK);
+    assert_eq!(res.text(), Some("posts of 42"));
+
+    let req = TestRequest::GET("/users/42/comments");
+    let res = t.oneshot(req).await;
+    assert_eq!(res.status(), Status::NotFound);
+}
+
+#[test]
+#[should_panic(expected = "Wildcard must be the last section: `/files/*path/raw`")]
+fn wildcard_not_at_last() {
+    Ohkami::new((
+        "/files/*path/raw".GET(|| async {"raw"}),
+    ));
+}
+
 #[__rt__::test]
 async fn prefixy_routes() {
     let t = Ohkami::new((
@@ -490,3 +1044,1035 @@ async fn prefixy_routes() {
         assert_eq!(res.status(), Status::NotFound);
     }
 }
+
+#[__rt__::test]
+async fn extension_methods() {
+    #[derive(Clone)]
+    struct Dav;
+    impl FangAction for Dav {
+        async fn back<'a>(&'a self, res: &'a mut Response) {
+            res.headers.set().custom("DAV", "1");
+        }
+    }
+
+    async fn method(req: &Request) -> String {
+        format!("{} {}", req.method, req.path.str())
+    }
+
+    let t = Ohkami::new((
+        "/".GET(method),
+        "/dav".By(Ohkami::with(Dav, (
+            "/:name"
+                .GET(|name: String| async move {name})
+                .method("PROPFIND", method)
+                .method("MKCOL", method),
+        ))),
+        "/search".method("QUERY", method),
+    )).test();
+
+    {
+        let req = TestRequest::method("PROPFIND", "/dav/docs");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::OK);
+        assert_eq!(res.text(), Some("PROPFIND /dav/docs"));
+        assert_eq!(res.header("DAV"), Some("1"));
+    }
+    {
+        let req = TestRequest::method("MKCOL", "/dav/new");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("MKCOL /dav/new"));
+    }
+    {
+        let req = TestRequest::method("QUERY", "/search");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("QUERY /search"));
+    }
+    {
+        let req = TestRequest::method("GET", "/");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("GET /"));
+    }
+    {
+        let req = TestRequest::method("PROPFIND", "/search");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::NotFound);
+    }
+    {
+        let req = TestRequest::method("LOCK", "/dav/docs");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::NotImplemented);
+    }
+    {
+        let req = TestRequest::OPTIONS("/dav/docs")
+            .header("Access-Control-Request-Method", "PROPFIND");
+        let res = t.oneshot(req).await;
+        assert_eq!(res.header("Access-Control-Allow-Methods"), Some("GET, PROPFIND, MKCOL, HEAD, OPTIONS"));
+    }
+}
+
+#[test]
+#[should_panic(expected = "`HEAD` is handled by Ohkami itself: `/abc`")]
+fn extension_method_registration() {
+    Ohkami::new((
+        "/abc".method("HEAD", || async {"HEAD"}),
+    ));
+}
+
+#[__rt__::test]
+async fn large_request_headers() {
+    async fn authorization_len(req: &Request) -> String {
+        req.headers.Authorization().map_or(0, str::len).to_string()
+    }
+
+    let t = Ohkami::new((
+        "/".GET(authorization_len),
+    )).test();
+
+    {
+        let req = TestRequest::GET("/")
+            .header("Authorization", format!("Bearer {}", "x".repeat(4000)));
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::OK);
+        assert_eq!(res.text(), Some("4007"));
+    }
+    {
+        let req = TestRequest::GET("/")
+            .header("Authorization", format!("Bearer {}", "x".repeat(10000)));
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::RequestHeaderFieldsTooLarge);
+    }
+}
+
+#[__rt__::test]
+async fn stream_request_body() {
+    use crate::format::BodyStream;
+    use crate::util::StreamExt;
+
+    async fn count_chunks(mut body: BodyStream<'_>) -> Result<String, Response> {
+        let (mut chunks, mut size) = (0, 0);
+        while let Some(chunk) = body.next().await {
+            chunks += 1;
+            size   += chunk?.len();
+        }
+        Ok(format!("{chunks} {size}"))
+    }
+
+    async fn payload_len(req: &Request) -> String {
+        req.payload().map_or(0, <[u8]>::len).to_string()
+    }
+
+    let t = Ohkami::new((
+        "/stream".POST(count_chunks),
+        "/buffer".POST(payload_len),
+    )).test();
+
+    {
+        let req = TestRequest::POST("/stream")
+            .content("application/octet-stream", vec![b'x'; 100_000]);
+        let res = t.oneshot(req).await;
+        assert_eq!(res.status(), Status::OK);
+        let (chunks, size) = res.text().unwrap().split_once(' ').unwrap();
+        assert!(chunks.parse::<usize>().unwrap() > 1);
+        assert_eq!(size, "100000");
+    }
+    {
+        let req = TestRequest::POST("/stream")
+            .content("application/octet-stream", b"Hello".to_vec());
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("1 5"));
+    }
+    {
+        let req = TestRequest::POST("/buffer")
+            .content("application/octet-stream", vec![b'x'; 100_000]);
+        let res = t.oneshot(req).await;
+        assert_eq!(res.text(), Some("100000"));
+    }
+}
+
+#[__rt__::test]
+async fn howl_with_bind_error() {
+    let occupied = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address  = occupied.local_addr().unwrap();
+
+    let howl = Ohkami::new((
+        "/".GET(|| async {"unreachable"}),
+    )).howl_with(address, crate::ServerConfig::default()).await;
+    assert!(howl.is_err());
+}
+
+#[cfg(feature="rt_tokio")]
+/// Bound to a free local port, to be served by `howl_on` as it is
+/// so that no one else takes the port in the meantime
+fn local_listener() -> (std::net::TcpListener, std::net::SocketAddr) {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    (listener, address)
+}
+
+#[cfg(feature="rt_tokio")]
+/// Serve `ohkami` with `config` on a local listener while `client` runs with its address
+async fn howl_while<Client: std::future::Future<Output = ()>>(
+    ohkami: Ohkami,
+    config: crate::ServerConfig,
+    client: impl FnOnce(std::net::SocketAddr) -> Client,
+) {
+    let (listener, address) = local_listener();
+    tokio::select! {
+        howl = ohkami.howl_on([listener], config, std::future::pending()) => panic!("`howl_on` exited unexpectedly: {howl:?}"),
+        _ = client(address) => (),
+    }
+}
+
+#[cfg(feature="rt_tokio")]
+/// Send `raw` on a new connection to `address` and read all until the connection is closed
+async fn request(address: std::net::SocketAddr, raw: &str) -> String {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut conn = tokio::net::TcpStream::connect(address).await.unwrap();
+    conn.write_all(raw.as_bytes()).await.unwrap();
+    let mut res = Vec::new();
+    conn.read_to_end(&mut res).await.unwrap();
+    String::from_utf8(res).unwrap()
+}
+
+#[cfg(feature="rt_tokio")]
+/// Read a response arriving at once on a kept connection, `""` if it's closed
+async fn read_response(conn: &mut (impl tokio::io::AsyncRead + Unpin)) -> String {
+    use tokio::io::AsyncReadExt;
+
+    let mut buf = vec![0; 1024];
+    let n = conn.read(&mut buf).await.unwrap();
+    String::from_utf8(buf[..n].to_vec()).unwrap()
+}
+
+#[cfg(all(feature="http2", feature="rt_tokio"))]
+#[__rt__::test]
+async fn howl_h2c_prior_knowledge() {
+
+    async fn echo(req: &Request) -> String {
+        String::from_utf8_lossy(req.payload().unwrap_or_default()).into_owned()
+    }
+    async fn cookie(req: &Request) -> String {
+        req.headers.Cookie().unwrap_or_default().to_owned()
+    }
+
+    howl_while(Ohkami::new((
+        "/hello".GET(|| async {"Hello, HTTP/2!"}),
+        "/large".GET(|| async {"a".repeat(200_000)}),
+        "/echo".POST(echo),
+        "/cookie".GET(cookie),
+        "/slow".GET(|| async {
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+            "slow"
+        }),
+    )), crate::ServerConfig {
+        max_payload_size:  1024,
+        keepalive_timeout: std::time::Duration::from_millis(200),
+        ..Default::default()
+    }, |address| async move {
+        let tcp = tokio::net::TcpStream::connect(address).await.unwrap();
+        let (mut client, h2) = ::h2::client::handshake(tcp).await.unwrap();
+        tokio::spawn(h2);
+
+        for _ in 0..2 {
+            let (res, _) = client.send_request(
+                ::http::Request::get("/hello").body(()).unwrap(),
+                true
+            ).unwrap();
+            let res = res.await.unwrap();
+            assert_eq!(res.status(), 200);
+
+            let mut body = res.into_body();
+            let mut text = Vec::new();
+            while let Some(data) = body.data().await {
+                text.extend_from_slice(&data.unwrap());
+            }
+            assert_eq!(text, b"Hello, HTTP/2!");
+        }
+
+        /* larger than the initial flow-control window ( 65535 ) */
+        let (res, _) = client.send_request(
+            ::http::Request::get("/large").body(()).unwrap(),
+            true
+        ).unwrap();
+        let res = res.await.unwrap();
+        assert_eq!(res.status(), 200);
+
+        let mut body = res.into_body();
+        let mut size = 0;
+        while let Some(data) = body.data().await {
+            let data = data.unwrap();
+            size += data.len();
+            body.flow_control().release_capacity(data.len()).unwrap();
+        }
+        assert_eq!(size, 200_000);
+
+        /* the body is read from the stream, framed by END_STREAM without `content-length` */
+        async fn post(client: &mut ::h2::client::SendRequest<::bytes::Bytes>, frames: &[&str]) -> (u16, Vec<u8>) {
+            let (res, mut body) = client.send_request(
+                ::http::Request::post("/echo").body(()).unwrap(),
+                false
+            ).unwrap();
+            for (i, frame) in frames.iter().enumerate() {
+                body.send_data(::bytes::Bytes::copy_from_slice(frame.as_bytes()), i == frames.len() - 1).unwrap();
+            }
+            let res = res.await.unwrap();
+            let status = res.status().as_u16();
+            let mut body = res.into_body();
+            let mut text = Vec::new();
+            while let Some(data) = body.data().await {
+                text.extend_from_slice(&data.unwrap());
+            }
+            (status, text)
+        }
+        assert_eq!(post(&mut client, &["Hello, ", "HTTP/2 ", "body!"]).await, (200, b"Hello, HTTP/2 body!".to_vec()));
+        let kb = "a".repeat(1000);
+        assert_eq!(post(&mut client, &[&kb, &kb]).await.0, 413);
+
+        let (res, _) = client.send_request(
+            ::http::Request::get("/cookie")
+                .header("cookie", "a=1")
+                .header("cookie", "b=2")
+                .body(()).unwrap(),
+            true
+        ).unwrap();
+        let mut body = res.await.unwrap().into_body();
+        let mut text = Vec::new();
+        while let Some(data) = body.data().await {
+            text.extend_from_slice(&data.unwrap());
+        }
+        assert_eq!(text, b"a=1; b=2");
+
+        /* `keepalive_timeout` doesn't close a connection while a stream is open */
+        let (slow, _) = client.send_request(
+            ::http::Request::get("/slow").body(()).unwrap(),
+            true
+        ).unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        let (res, _) = client.send_request(
+            ::http::Request::get("/hello").body(()).unwrap(),
+            true
+        ).unwrap();
+        assert_eq!(res.await.unwrap().status(), 200);
+        let mut body = slow.await.unwrap().into_body();
+        let mut text = Vec::new();
+        while let Some(data) = body.data().await {
+            text.extend_from_slice(&data.unwrap());
+        }
+        assert_eq!(text, b"slow");
+
+        /* HTTP/1.1 starting like the preface is still served, with the bytes read to tell it */
+        {
+            use tokio::io::AsyncWriteExt;
+
+            let mut conn = tokio::net::TcpStream::connect(address).await.unwrap();
+            conn.write_all(b"P").await.unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            conn.write_all(b"OST /echo HTTP/1.1\r\nContent-Length: 2\r\n\r\nhi").await.unwrap();
+
+            let res = read_response(&mut conn).await;
+            assert!(res.starts_with("HTTP/1.1 200 OK\r\n"), "{res}");
+            assert!(res.ends_with("\r\n\r\nhi"), "{res}");
+        }
+    }).await;
+}
+
+#[cfg(feature="rt_tokio")]
+#[__rt__::test]
+async fn howl_keepalive_and_read_timeouts() {
+    use tokio::io::AsyncWriteExt;
+    use std::time::Duration;
+
+    async fn echo(req: &Request) -> String {
+        String::from_utf8_lossy(req.payload().unwrap_or_default()).into_owned()
+    }
+
+    howl_while(Ohkami::new((
+        "/".GET(|| async {"Hello!"}),
+        "/echo".POST(echo),
+    )), crate::ServerConfig {
+        keepalive_timeout:   Duration::from_millis(300),
+        header_read_timeout: Duration::from_millis(100),
+        body_read_timeout:   Duration::from_millis(100),
+        ..Default::default()
+    }, |address| async move {
+        /* a busy connection outlives `keepalive_timeout` */
+        let mut conn = tokio::net::TcpStream::connect(address).await.unwrap();
+        for _ in 0..4 {
+            conn.write_all(b"GET / HTTP/1.1\r\n\r\n").await.unwrap();
+            let res = read_response(&mut conn).await;
+            assert!(res.starts_with("HTTP/1.1 200 OK\r\n"), "{res}");
+            assert!(res.ends_with("\r\n\r\nHello!"), "{res}");
+
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+
+        /* an idle connection is closed after `keepalive_timeout` */
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(read_response(&mut conn).await, "");
+
+        /* an incomplete request head is responded with 408 */
+        let mut conn = tokio::net::TcpStream::connect(address).await.unwrap();
+        conn.write_all(b"GET / HTTP/1.1\r\n").await.unwrap();
+        let res = read_response(&mut conn).await;
+        assert!(res.starts_with("HTTP/1.1 408 Request Timeout\r\n"), "{res}");
+        assert_eq!(read_response(&mut conn).await, "");
+
+        /* a body sent slowly but steadily outlives `body_read_timeout`, as it's per read */
+        let mut conn = tokio::net::TcpStream::connect(address).await.unwrap();
+        conn.write_all(b"POST /echo HTTP/1.1\r\nContent-Length: 4\r\n\r\n").await.unwrap();
+        for b in b"abcd" {
+            tokio::time::sleep(Duration::from_millis(60)).await;
+            conn.write_all(&[*b]).await.unwrap();
+        }
+        let res = read_response(&mut conn).await;
+        assert!(res.starts_with("HTTP/1.1 200 OK\r\n"), "{res}");
+        assert!(res.ends_with("\r\n\r\nabcd"), "{res}");
+
+        /* a stalled body is responded with 408 */
+        let mut conn = tokio::net::TcpStream::connect(address).await.unwrap();
+        conn.write_all(b"POST /echo HTTP/1.1\r\nContent-Length: 4\r\n\r\nab").await.unwrap();
+        let res = read_response(&mut conn).await;
+        assert!(res.starts_with("HTTP/1.1 408 Request Timeout\r\n"), "{res}");
+        assert_eq!(read_response(&mut conn).await, "");
+    }).await;
+}
+
+#[cfg(feature="rt_tokio")]
+#[__rt__::test]
+async fn howl_pipelining() {
+    async fn echo(req: &Request) -> String {
+        format!("{} {}", req.path.str(), req.payload().map_or(0, <[u8]>::len))
+    }
+
+    howl_while(Ohkami::new((
+        "/a".GET(echo),
+        "/b".POST(echo),
+        "/c".GET(echo),
+    )), crate::ServerConfig::default(), |address| async move {
+        let res = request(address, "\
+            GET /a HTTP/1.1\r\n\r\n\
+            POST /b HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello\
+            GET /c HTTP/1.1\r\nConnection: close\r\n\r\n\
+        ").await;
+
+        let bodies = res.split("HTTP/1.1 200 OK\r\n").skip(1)
+            .map(|r| r.split_once("\r\n\r\n").unwrap().1)
+            .collect::<Vec<_>>();
+        assert_eq!(bodies, ["/a 0", "/b 5", "/c 0"]);
+    }).await;
+}
+
+#[cfg(feature="rt_tokio")]
+#[__rt__::test]
+async fn howl_expect_continue() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[derive(Clone)]
+    struct LimitUpload(usize);
+    impl FangAction for LimitUpload {
+        async fn fore<'a>(&'a self, req: &'a mut Request) -> Result<(), Response> {
+            match req.headers.ContentLength().and_then(|len| len.parse::<usize>().ok()) {
+                Some(len) if len > self.0 => Err(Response::PayloadTooLarge()),
+                _ => Ok(())
+            }
+        }
+    }
+
+    async fn echo(req: &Request) -> String {
+        format!("{} {}", req.path.str(), req.payload().map_or(0, <[u8]>::len))
+    }
+
+    howl_while(Ohkami::with(LimitUpload(10), (
+        "/upload".POST(echo),
+    )), crate::ServerConfig::default(), |address| async move {
+        /* the body is sent after `100 Continue` */
+        let mut conn = tokio::net::TcpStream::connect(address).await.unwrap();
+        conn.write_all(b"POST /upload HTTP/1.1\r\nContent-Length: 5\r\nExpect: 100-continue\r\nConnection: close\r\n\r\n").await.unwrap();
+        let mut interim = [0; "HTTP/1.1 100 Continue\r\n\r\n".len()];
+        conn.read_exact(&mut interim).await.unwrap();
+        assert_eq!(&interim, b"HTTP/1.1 100 Continue\r\n\r\n");
+        conn.write_all(b"hello").await.unwrap();
+        let mut res = String::new();
+        conn.read_to_string(&mut res).await.unwrap();
+        assert!(res.starts_with("HTTP/1.1 200 OK\r\n"), "{res}");
+        assert!(res.ends_with("/upload 5"), "{res}");
+
+        for (head, status) in [
+            ("POST /upload HTTP/1.1\r\nContent-Length: 100\r\nExpect: 100-continue\r\n\r\n", "413 Payload Too Large"),
+            ("POST /nowhere HTTP/1.1\r\nContent-Length: 5\r\nExpect: 100-continue\r\n\r\n", "404 Not Found"),
+            ("POST /upload HTTP/1.1\r\nContent-Length: 5\r\nExpect: something\r\n\r\n", "417 Expectation Failed"),
+        ] {
+            let res = request(address, head).await;
+            assert!(res.starts_with(&format!("HTTP/1.1 {status}\r\n")), "{res}");
+        }
+    }).await;
+}
+
+#[cfg(feature="rt_tokio")]
+#[__rt__::test]
+async fn howl_http10() {
+    use tokio::io::AsyncWriteExt;
+
+    async fn echo(req: &Request) -> String {
+        format!("{} {}", req.authority().unwrap_or("-"), req.path.str())
+    }
+
+    howl_while(Ohkami::new((
+        "/hello".GET(echo),
+    )), crate::ServerConfig::default(), |address| async move {
+        /* closed after the response by default */
+        let res = request(address, "GET /hello HTTP/1.0\r\n\r\n").await;
+        assert!(res.starts_with("HTTP/1.1 200 OK\r\n"), "{res}");
+        assert!(res.contains("\r\nConnection: close\r\n"), "{res}");
+        assert!(res.ends_with("- /hello"), "{res}");
+
+        /* kept with `Connection: keep-alive` */
+        let mut conn = tokio::net::TcpStream::connect(address).await.unwrap();
+        conn.write_all(b"GET http://example.com/hello HTTP/1.0\r\nConnection: keep-alive\r\n\r\n").await.unwrap();
+        let res = read_response(&mut conn).await;
+        assert!(res.contains("\r\nConnection: keep-alive\r\n"), "{res}");
+        assert!(res.ends_with("example.com /hello"), "{res}");
+
+        conn.write_all(b"GET /hello HTTP/1.0\r\n\r\n").await.unwrap();
+        let res = read_response(&mut conn).await;
+        assert!(res.starts_with("HTTP/1.1 200 OK\r\n"), "{res}");
+        assert!(res.ends_with("- /hello"), "{res}");
+        assert_eq!(read_response(&mut conn).await, "");
+    }).await;
+}
+
+#[cfg(all(feature="rt_tokio", feature="sse"))]
+#[__rt__::test]
+async fn howl_http10_stream() {
+    use crate::typed::DataStream;
+
+    async fn events() -> DataStream<&'static str> {
+        DataStream::from_iter([Ok("Hello"), Ok("ohkami")])
+    }
+
+    howl_while(Ohkami::new((
+        "/events".GET(events),
+    )), crate::ServerConfig::default(), |address| async move {
+        /* not chunked but closed at the end, even with `Connection: keep-alive` */
+        let res = request(address, "GET /events HTTP/1.0\r\nConnection: keep-alive\r\n\r\n").await;
+        assert!(res.starts_with("HTTP/1.1 200 OK\r\n"), "{res}");
+        assert!(res.contains("\r\nConnection: close\r\n"), "{res}");
+        assert!(!res.contains("Transfer-Encoding"), "{res}");
+        assert!(res.ends_with("\r\n\r\ndata: Hello\n\ndata: ohkami\n\n"), "{res}");
+
+        /* chunked for HTTP/1.1 */
+        let res = request(address, "GET /events HTTP/1.1\r\nConnection: close\r\n\r\n").await;
+        assert!(res.contains("\r\nTransfer-Encoding: chunked\r\n"), "{res}");
+        assert!(res.ends_with("\r\n0\r\n\r\n"), "{res}");
+    }).await;
+}
+
+#[cfg(feature="rt_tokio")]
+#[__rt__::test]
+async fn howl_unknown_method() {
+    howl_while(Ohkami::new((
+        "/dav".method("PROPFIND", || async {"propfind"}),
+    )), crate::ServerConfig::default(), |address| async move {
+        for (head, status) in [
+            ("PROPFIND /dav HTTP/1.1\r\nConnection: close\r\n\r\n", "200 OK"),
+            ("BREW /pot HTTP/1.1\r\nConnection: close\r\n\r\n", "501 Not Implemented"),
+            ("A_VERY_LONG_EXTENSION_METHOD / HTTP/1.1\r\n\r\n", "501 Not Implemented"),
+            ("GE(T / HTTP/1.1\r\n\r\n", "400 Bad Request"),
+        ] {
+            let res = request(address, head).await;
+            assert!(res.starts_with(&format!("HTTP/1.1 {status}\r\n")), "{res}");
+        }
+    }).await;
+}
+
+#[cfg(feature="rt_tokio")]
+#[__rt__::test]
+async fn howl_until_shutdown() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use std::time::{Duration, Instant};
+
+    async fn wait(shutdown: crate::Shutdown) -> &'static str {
+        shutdown.notified().await;
+        "notified"
+    }
+
+    async fn stuck() -> &'static str {
+        std::future::pending().await
+    }
+
+    let (listener, address) = local_listener();
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+
+    let howl = Ohkami::new((
+        "/".GET(|| async {"Hello"}),
+        "/wait".GET(wait),
+        "/stuck".GET(stuck),
+    )).howl_on([listener], crate::ServerConfig {
+        shutdown_timeout: Duration::from_millis(500),
+        ..Default::default()
+    }, async {shutdown_rx.await.ok();});
+
+    let client = async {
+        let mut idle = tokio::net::TcpStream::connect(address).await.unwrap();
+        idle.write_all(b"GET / HTTP/1.1\r\n\r\n").await.unwrap();
+        let res = read_response(&mut idle).await;
+        assert!(res.ends_with("Hello"), "{res}");
+
+        let mut waiting = tokio::net::TcpStream::connect(address).await.unwrap();
+        waiting.write_all(b"GET /wait HTTP/1.1\r\n\r\n").await.unwrap();
+
+        let mut stucking = tokio::net::TcpStream::connect(address).await.unwrap();
+        stucking.write_all(b"GET /stuck HTTP/1.1\r\n\r\n").await.unwrap();
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let start = Instant::now();
+        shutdown_tx.send(()).unwrap();
+
+        /* an idle connection is closed at once */
+        assert_eq!(read_response(&mut idle).await, "");
+        assert!(start.elapsed() < Duration::from_millis(300));
+
+        /* a handler taking `Shutdown` is notified, and the connection is closed after the response */
+        let mut res = String::new();
+        waiting.read_to_string(&mut res).await.unwrap();
+        assert!(res.starts_with("HTTP/1.1 200 OK\r\n"), "{res}");
+        assert!(res.contains("\r\nConnection: close\r\n"), "{res}");
+        assert!(res.ends_with("notified"), "{res}");
+
+        /* a stuck one is force-closed after `shutdown_timeout` */
+        assert_eq!(stucking.read(&mut [0; 1024]).await.unwrap_or(0), 0);
+        assert!(start.elapsed() >= Duration::from_millis(500));
+
+        start
+    };
+
+    let (howl, start) = tokio::join!(howl, client);
+    howl.unwrap();
+    assert!(start.elapsed() < Duration::from_secs(1), "`howl_on` didn't finish soon after `shutdown_timeout`");
+    assert!(tokio::net::TcpStream::connect(address).await.is_err());
+}
+
+#[cfg(all(unix, feature="rt_tokio"))]
+#[__rt__::test]
+async fn howl_unix_socket() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use std::time::Duration;
+
+    async fn ip(req: &Request) -> String {
+        format!("{:?}", req.ip)
+    }
+
+    let path = std::env::temp_dir().join(format!("ohkami-test-{}.sock", std::process::id()));
+
+    /* a stale socket file left by a previous process */
+    drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+    assert!(path.exists());
+
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+
+    let howl = Ohkami::new((
+        "/ip".GET(ip),
+    )).howl_unix_until(&path, crate::ServerConfig::default(), async {shutdown_rx.await.ok();});
+
+    let client = async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let mut conn = tokio::net::UnixStream::connect(&path).await.unwrap();
+        conn.write_all(b"GET /ip HTTP/1.1\r\nConnection: close\r\n\r\n").await.unwrap();
+        let mut res = Vec::new();
+        conn.read_to_end(&mut res).await.unwrap();
+        let res = String::from_utf8(res).unwrap();
+        assert!(res.starts_with("HTTP/1.1 200 OK\r\n"), "{res}");
+        assert!(res.ends_with("None"), "{res}");
+
+        /* in use */
+        let e = Ohkami::new(()).howl_unix_until(&path, crate::ServerConfig::default(), async {}).await.unwrap_err();
+        assert_eq!(e.kind(), std::io::ErrorKind::AddrInUse);
+
+        shutdown_tx.send(()).unwrap();
+    };
+
+    let (howl, ()) = tokio::join!(howl, client);
+    howl.unwrap();
+    assert!(!path.exists());
+}
+
+#[cfg(feature="rt_tokio")]
+#[__rt__::test]
+async fn howl_on_prebound_listeners() {
+    /* bound to port 0 and the actual ports are known before starting the server */
+    let (l1, l2) = (
+        std::net::TcpListener::bind("127.0.0.1:0").unwrap(),
+        std::net::TcpListener::bind("127.0.0.1:0").unwrap(),
+    );
+    let (a1, a2) = (l1.local_addr().unwrap(), l2.local_addr().unwrap());
+
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+
+    let howl = Ohkami::new((
+        "/hello".GET(|| async {"Hello, pre-bound!"}),
+    )).howl_on([l1, l2], crate::ServerConfig::default(), async {shutdown_rx.await.ok();});
+
+    let client = async {
+        for addr in [a1, a2] {
+            let res = request(addr, "GET /hello HTTP/1.1\r\nConnection: close\r\n\r\n").await;
+            assert!(res.starts_with("HTTP/1.1 200 OK\r\n"), "{res}");
+            assert!(res.ends_with("Hello, pre-bound!"), "{res}");
+        }
+        shutdown_tx.send(()).unwrap();
+    };
+
+    let (howl, ()) = tokio::join!(howl, client);
+    howl.unwrap();
+
+    /* listeners are closed after shutdown */
+    assert!(tokio::net::TcpStream::connect(a1).await.is_err());
+
+    let e = Ohkami::new(()).howl_on(Vec::<crate::Listener>::new(), crate::ServerConfig::default(), async {}).await.unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput);
+
+    /* not socket-activated */
+    #[cfg(unix)]
+    assert!(crate::Listener::systemd().unwrap().is_empty());
+}
+
+#[cfg(feature="rt_tokio")]
+#[__rt__::test]
+async fn howl_on_multiple_listeners() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    async fn which(req: &Request) -> String {
+        let listener = req.listener().unwrap();
+        format!("{} {:?}", listener.index(), listener.local_addr())
+    }
+
+    async fn get(conn: &mut (impl tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin)) -> String {
+        conn.write_all(b"GET /which HTTP/1.1\r\nConnection: close\r\n\r\n").await.unwrap();
+        let mut res = Vec::new();
+        conn.read_to_end(&mut res).await.unwrap();
+        String::from_utf8(res).unwrap()
+    }
+
+    let (l1, l2) = (
+        std::net::TcpListener::bind("127.0.0.1:0").unwrap(),
+        std::net::TcpListener::bind("127.0.0.1:0").unwrap(),
+    );
+    let (a1, a2) = (l1.local_addr().unwrap(), l2.local_addr().unwrap());
+    let mut listeners = vec![crate::Listener::from(l1), crate::Listener::from(l2)];
+
+    #[cfg(unix)]
+    let path = std::env::temp_dir().join(format!("ohkami-multiple-listeners-{}.sock", std::process::id()));
+    #[cfg(unix)] {
+        let _ = std::fs::remove_file(&path);
+        listeners.push(std::os::unix::net::UnixListener::bind(&path).unwrap().into());
+    }
+
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+
+    let howl = Ohkami::new((
+        "/which".GET(which),
+    )).howl_on(listeners, crate::ServerConfig::default(), async {shutdown_rx.await.ok();});
+
+    let client = async {
+        for (index, addr) in [a1, a2].into_iter().enumerate() {
+            let res = get(&mut tokio::net::TcpStream::connect(addr).await.unwrap()).await;
+            assert!(res.ends_with(&format!("{index} Tcp({addr:?})")), "{res}");
+        }
+        #[cfg(unix)] {
+            let res = get(&mut tokio::net::UnixStream::connect(&path).await.unwrap()).await;
+            assert!(res.ends_with(&format!("2 Unix(Some({path:?}))")), "{res}");
+        }
+        shutdown_tx.send(()).unwrap();
+    };
+
+    let (howl, ()) = tokio::join!(howl, client);
+    howl.unwrap();
+
+    #[cfg(unix)]
+    let _ = std::fs::remove_file(&path);
+
+    let e = Ohkami::new(()).howl_until(Vec::<&str>::new(), crate::ServerConfig::default(), async {}).await.unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[cfg(feature="rt_tokio")]
+#[__rt__::test]
+async fn howl_connection_limits() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use std::time::Duration;
+
+    async fn slow() -> &'static str {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        "slow"
+    }
+
+    /* max_connections */
+    {
+        let (listener, address) = local_listener();
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+
+        let howl = Ohkami::new((
+            "/".GET(|| async {"Hello!"}),
+        )).howl_on([listener], crate::ServerConfig {
+            max_connections: Some(1),
+            ..Default::default()
+        }, async {shutdown_rx.await.ok();});
+
+        let client = async {
+            let mut first = tokio::net::TcpStream::connect(address).await.unwrap();
+            first.write_all(b"GET / HTTP/1.1\r\n\r\n").await.unwrap();
+            assert!(read_response(&mut first).await.starts_with("HTTP/1.1 200 OK\r\n"));
+
+            /* waiting in the backlog while `first` is alive */
+            let mut second = tokio::net::TcpStream::connect(address).await.unwrap();
+            second.write_all(b"GET / HTTP/1.1\r\nConnection: close\r\n\r\n").await.unwrap();
+            let mut buf = [0; 1];
+            assert!(tokio::time::timeout(Duration::from_millis(200), second.read(&mut buf)).await.is_err());
+
+            drop(first);
+            let res = tokio::time::timeout(Duration::from_secs(1), read_response(&mut second)).await.unwrap();
+            assert!(res.starts_with("HTTP/1.1 200 OK\r\n"), "{res}");
+
+            shutdown_tx.send(()).unwrap();
+        };
+
+        let (howl, ()) = tokio::join!(howl, client);
+        howl.unwrap();
+    }
+
+    /* max_requests_in_flight */
+    {
+        let (listener, address) = local_listener();
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+
+        let howl = Ohkami::new((
+            "/slow".GET(slow),
+        )).howl_on([listener], crate::ServerConfig {
+            max_requests_in_flight: Some(1),
+            retry_after:            Duration::from_millis(2500),
+            ..Default::default()
+        }, async {shutdown_rx.await.ok();});
+
+        let client = async {
+            let (mut a, mut b) = (
+                tokio::net::TcpStream::connect(address).await.unwrap(),
+                tokio::net::TcpStream::connect(address).await.unwrap(),
+            );
+            a.write_all(b"GET /slow HTTP/1.1\r\n\r\n").await.unwrap();
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            b.write_all(b"GET /slow HTTP/1.1\r\n\r\n").await.unwrap();
+
+            let res = read_response(&mut b).await;
+            assert!(res.starts_with("HTTP/1.1 503 Service Unavailable\r\n"), "{res}");
+            assert!(res.contains("Retry-After: 3\r\n"), "{res}");
+
+            let res = read_response(&mut a).await;
+            assert!(res.starts_with("HTTP/1.1 200 OK\r\n"), "{res}");
+
+            /* released */
+            b.write_all(b"GET /slow HTTP/1.1\r\n\r\n").await.unwrap();
+            let res = read_response(&mut b).await;
+            assert!(res.starts_with("HTTP/1.1 200 OK\r\n"), "{res}");
+
+            shutdown_tx.send(()).unwrap();
+        };
+
+        let (howl, ()) = tokio::join!(howl, client);
+        howl.unwrap();
+    }
+}
+
+#[cfg(feature="rt_tokio")]
+#[__rt__::test]
+async fn howl_connection_errors() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use crate::{ConnectionPhase, OnConnectionError};
+
+    async fn upload(req: &Request) -> String {
+        format!("uploaded {} bytes", req.payload().map_or(0, |p| p.len()))
+    }
+
+    let (listener, address) = local_listener();
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+
+    let errors = Arc::new(Mutex::new(Vec::new()));
+
+    let howl = Ohkami::new((
+        "/upload".POST(upload),
+    )).howl_on([listener], crate::ServerConfig {
+        on_connection_error: Some(OnConnectionError::new({
+            let errors = errors.clone();
+            move |e| errors.lock().unwrap().push((e.phase, e.ip, e.error.kind()))
+        })),
+        ..Default::default()
+    }, async {shutdown_rx.await.ok();});
+
+    let client = async {
+        /* going away in the middle of an upload */
+        {
+            let mut conn = tokio::net::TcpStream::connect(address).await.unwrap();
+            conn.write_all(b"\
+                POST /upload HTTP/1.1\r\n\
+                Content-Type: text/plain\r\n\
+                Content-Length: 100\r\n\
+                \r\n\
+                only 20 bytes here..\
+            ").await.unwrap();
+            conn.shutdown().await.unwrap();
+            let mut res = Vec::new();
+            conn.read_to_end(&mut res).await.unwrap();
+            assert!(res.starts_with(b"HTTP/1.1 400 Bad Request\r\n"), "{}", res.escape_ascii());
+        }
+
+        /* just closing an idle connection is not an error */
+        {
+            let mut conn = tokio::net::TcpStream::connect(address).await.unwrap();
+            conn.write_all(b"POST /upload HTTP/1.1\r\nContent-Type: text/plain\r\nContent-Length: 5\r\n\r\nhello").await.unwrap();
+            let res = read_response(&mut conn).await;
+            assert!(res.ends_with("uploaded 5 bytes"), "{res}");
+        }
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        shutdown_tx.send(()).unwrap();
+    };
+
+    let (howl, ()) = tokio::join!(howl, client);
+    howl.unwrap();
+
+    assert_eq!(*errors.lock().unwrap(), [(
+        ConnectionPhase::Read,
+        Some(std::net::IpAddr::from([127, 0, 0, 1])),
+        std::io::ErrorKind::UnexpectedEof,
+    )]);
+}
+
+#[__rt__::test]
+async fn on_panic() {
+    #[derive(Clone)]
+    struct PanicOnSecret;
+    impl FangAction for PanicOnSecret {
+        async fn fore<'a>(&'a self, req: &'a mut Request) -> Result<(), Response> {
+            if req.headers.custom("X-Secret").is_some() {
+                panic!("secret in fang")
+            }
+            Ok(())
+        }
+    }
+
+    async fn boom() -> &'static str {
+        /* panicking after an await, not on creating the future */
+        __rt__::sleep(std::time::Duration::from_millis(1)).await;
+        panic!("boom")
+    }
+
+    fn report(info: &crate::PanicInfo, req: &Request) -> Response {
+        Response::InternalServerError().with_text(format!("{} {}: {}",
+            req.method, req.path.str(), info.message().unwrap_or("?")
+        ))
+    }
+
+    let t = Ohkami::with(PanicOnSecret, (
+        "/boom".GET(boom),
+        "/api".By(Ohkami::new((
+            "/boom".GET(boom),
+        )).on_panic(|_, _| Response::ServiceUnavailable())),
+        "/plain".By(Ohkami::new((
+            "/boom".GET(boom),
+        ))),
+    )).on_panic(report).test();
+
+    {
+        let res = t.oneshot(TestRequest::GET("/boom")).await;
+        assert_eq!(res.status(), Status::InternalServerError);
+        assert_eq!(res.text(), Some("GET /boom: boom"));
+    }
+    {
+        let res = t.oneshot(TestRequest::GET("/boom").header("X-Secret", "1")).await;
+        assert_eq!(res.text(), Some("GET /boom: secret in fang"));
+    }
+    {
+        /* the innermost `on_panic` */
+        let res = t.oneshot(TestRequest::GET("/api/boom")).await;
+        assert_eq!(res.status(), Status::ServiceUnavailable);
+    }
+    {
+        let res = t.oneshot(TestRequest::GET("/plain/boom")).await;
+        assert_eq!(res.text(), Some("GET /plain/boom: boom"));
+    }
+
+    let location = Ohkami::new((
+        "/where".GET(|| async {
+            if true {panic!("where")}
+            "unreachable"
+        }),
+    )).on_panic(|info, _| Response::OK().with_text(info.location().unwrap_or_default().to_string()))
+        .test().oneshot(TestRequest::GET("/where")).await;
+    assert!(location.text().unwrap().starts_with(file!()), "{:?}", location.text());
+}
+
+#[cfg(feature="rt_tokio")]
+#[__rt__::test]
+async fn howl_proxy_protocol() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use std::sync::{Arc, Mutex};
+
+    async fn client(req: &Request) -> String {
+        format!("{:?} via {:?}", req.ip, req.proxy().and_then(|p| p.destination))
+    }
+
+    let (listener, address) = local_listener();
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+
+    let errors = Arc::new(Mutex::new(Vec::new()));
+
+    let howl = Ohkami::new((
+        "/client".GET(client),
+    )).howl_on([listener], crate::ServerConfig {
+        proxy_protocol: true,
+        on_connection_error: Some(crate::OnConnectionError::new({
+            let errors = errors.clone();
+            move |e| errors.lock().unwrap().push((e.phase, e.error.kind()))
+        })),
+        ..Default::default()
+    }, async {shutdown_rx.await.ok();});
+
+    let client = async {
+        {
+            let mut conn = tokio::net::TcpStream::connect(address).await.unwrap();
+            conn.write_all(b"\
+                PROXY TCP4 192.0.2.1 198.51.100.1 56324 443\r\n\
+                GET /client HTTP/1.1\r\n\
+                \r\n\
+            ").await.unwrap();
+            let res = read_response(&mut conn).await;
+            assert!(res.ends_with("Some(192.0.2.1) via Some(198.51.100.1:443)"), "{res}");
+
+            /* the header is only at the beginning of the connection */
+            conn.write_all(b"GET /client HTTP/1.1\r\nConnection: close\r\n\r\n").await.unwrap();
+            let mut res = Vec::new();
+            conn.read_to_end(&mut res).await.unwrap();
+            assert!(res.ends_with(b"Some(192.0.2.1) via Some(198.51.100.1:443)"), "{}", res.escape_ascii());
+        }
+        {
+            let mut conn = tokio::net::TcpStream::connect(address).await.unwrap();
+            conn.write_all(b"GET /client HTTP/1.1\r\nConnection: close\r\n\r\n").await.unwrap();
+            /* closed without any response, maybe reset as the request is left unread */
+            let mut res = Vec::new();
+            if let Err(e) = conn.read_to_end(&mut res).await {
+                assert_eq!(e.kind(), std::io::ErrorKind::ConnectionReset);
+            }
+            assert!(res.is_empty(), "{}", res.escape_ascii());
+        }
+        shutdown_tx.send(()).unwrap();
+    };
+
+    let (howl, ()) = tokio::join!(howl, client);
+    howl.unwrap();
+
+    assert_eq!(*errors.lock().unwrap(), [
+        (crate::ConnectionPhase::Handshake, std::io::ErrorKind::InvalidData),
+    ]);
+}
@@ -3,7 +3,7 @@
 use super::router::{TrieRouter, RouteSections};
 use crate::fang::{Handler, IntoHandler};
 use crate::response::Content;
-use crate::Ohkami;
+use crate::{Ohkami, Method, ExtensionMethod};
 
 
 macro_rules! Handlers {
@@ -13,6 +13,7 @@ macro_rules! Handlers {
             $(
                 pub(crate) $method: Option<Handler>,
             )*
+            pub(crate) extensions: Vec<(ExtensionMethod, Handler)>,
         }
         
         impl Handlers {
@@ -22,6 +23,7 @@ macro_rules! Handlers {
                     $(
                         $method: None,
                     )*
+                    extensions: Vec::new(),
                 }
             }
         }
@@ -33,6 +35,32 @@ macro_rules! Handlers {
                     self
                 }
             )*
+
+            /// Register `handler` for `method` given by name, typically an extension method
+            /// like WebDAV's `PROPFIND` or `QUERY`.
+            /// 
+            /// Panics if `method` is not a valid method name, or is `HEAD` or `OPTIONS`,
+            /// which Ohkami handles by itself.
+            pub fn method<T>(mut self, method: &str, handler: impl IntoHandler<T>) -> Self {
+                let handler = handler.into_handler();
+                match Method::from_bytes(method.as_bytes()) {
+                    $(
+                        Some(Method::$method) => {self.$method.replace(handler);}
+                    )*
+                    Some(Method::Extension(ext)) => match self.extensions.iter_mut().find(|(m, _)| *m == ext) {
+                        Some((_, h)) => *h = handler,
+                        None => self.extensions.push((ext, handler)),
+                    }
+                    Some(Method::HEAD | Method::OPTIONS) => panic!(
+                        "`{method}` is handled by Ohkami itself: `{}`", self.route.literal()
+                    ),
+                    None => panic!(
+                        "Invalid method `{method}` ( must be a token of at most {} bytes ): `{}`",
+                        ExtensionMethod::MAX_LEN, self.route.literal()
+                    ),
+                }
+                self
+            }
         }
     };
 } Handlers! { GET, PUT, POST, PATCH, DELETE }
@@ -160,6 +188,8 @@ macro_rules! Route {
         ///         "/hello"  // <-- `Route` works here...
         ///             .GET(greet)
         ///             .PUT(hello),
+        ///         "/dav"
+        ///             .method("PROPFIND", hello),
         ///     )).howl("localhost:3000").await
         /// }
         /// ```
@@ -168,6 +198,10 @@ macro_rules! Route {
                 fn $method<T>(self, handler: impl IntoHandler<T>) -> Handlers;
             )*
 
+            /// Route by `method` given by name, typically an extension method
+            /// like WebDAV's `PROPFIND` or `QUERY`. See `Handlers::method`.
+            fn method<T>(self, method: &str, handler: impl IntoHandler<T>) -> Handlers;
+
             fn By(self, another: Ohkami) -> ByAnother;
 
             fn Dir(self, static_files_dir_path: &'static str) -> Dir;
@@ -182,6 +216,10 @@ macro_rules! Route {
                 }
             )*
 
+            fn method<T>(self, method: &str, handler: impl IntoHandler<T>) -> Handlers {
+                Handlers::new(self).method(method, handler)
+            }
+
             fn By(self, another: Ohkami) -> ByAnother {
                 ByAnother {
                     route:  RouteSections::from_literal(self),
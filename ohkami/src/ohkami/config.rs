@@ -0,0 +1,334 @@
+#![cfg(feature="__rt_native__")]
+
+use std::{io, net::IpAddr, sync::Arc, time::Duration};
+
+
+/// # Server configuration for `Ohkami::howl_with`
+///
+/// <br>
+///
+/// *example.rs*
+/// ```no_run
+/// use ohkami::prelude::*;
+/// use ohkami::ServerConfig;
+/// use std::time::Duration;
+///
+/// #[tokio::main]
+/// async fn main() -> std::io::Result<()> {
+///     Ohkami::new((
+///         "/".GET(|| async {"Hello, config!"}),
+///     )).howl_with("0.0.0.0:3000", ServerConfig::default()
+///         .with_keepalive_timeout(Duration::from_secs(5))
+///         .with_max_payload_size(1 << 20)
+///     ).await
+/// }
+/// ```
+///
+/// ---
+///
+/// `Default` respects `OHKAMI_KEEPALIVE_TIMEOUT` and `OHKAMI_WEBSOCKET_TIMEOUT`
+/// environment variables ( in seconds ) if they are set.
+///
+/// This is `#[non_exhaustive]` to add fields in the future : create it by `Default`,
+/// and set fields by the `with_*` methods or assignments.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct ServerConfig {
+    /// Timeout of an idle keep-alive connection waiting for the next request ( default: 42 seconds )
+    pub keepalive_timeout:   Duration,
+
+    /// Timeout of reading the rest of a request head after its first bytes arrived,
+    /// responding `408 Request Timeout` on expiry ( default: 30 seconds )
+    pub header_read_timeout: Duration,
+
+    /// Timeout of each read of a request body, responding `408 Request Timeout` on expiry ( default: 30 seconds )
+    ///
+    /// This is how long the body may stay idle, not a deadline for the whole body,
+    /// so a large but steadily sent body isn't cut off. It's the same whether the body is
+    /// buffered before the handler or read by `BodyStream`.
+    pub body_read_timeout:   Duration,
+
+    #[cfg(feature="ws")]
+    /// Timeout of a WebSocket session ( default: 1 hour )
+    pub websocket_timeout:   Duration,
+
+    /// Max size of request line and headers in bytes, responding `431 Request Header Fields Too Large`
+    /// beyond it ( default: 8 KiB )
+    ///
+    /// The buffer for them starts from 1 KiB and grows up to this as needed.
+    pub max_header_size:     usize,
+
+    /// Max size of a request payload in bytes ( default: 4 GiB )
+    pub max_payload_size:    usize,
+
+    /// Max length of the queue of pending connections ( default: 1024 )
+    pub backlog:             u32,
+
+    /// Max number of concurrent connections ( default: `None`, unlimited )
+    ///
+    /// Beyond it, the server stops accepting until a connection is closed,
+    /// and new connections wait in the `backlog`.
+    pub max_connections:     Option<usize>,
+
+    /// Max number of requests handled at the same time ( default: `None`, unlimited )
+    ///
+    /// Beyond it, the server responds `503 Service Unavailable` with `Retry-After`
+    /// of `retry_after` without calling the handler.
+    pub max_requests_in_flight: Option<usize>,
+
+    /// `Retry-After` of `503 Service Unavailable` by `max_requests_in_flight`,
+    /// rounded up to seconds ( default: 1 second )
+    pub retry_after:         Duration,
+
+    /// Time to wait for running sessions to finish after graceful shutdown is started,
+    /// force-closing the remaining connections on expiry ( default: 30 seconds )
+    pub shutdown_timeout:    Duration,
+
+    /// Whether to expect a PROXY protocol ( v1 or v2 ) header at the beginning
+    /// of every connection, sent by a proxy or load balancer in front of Ohkami
+    /// ( default: `false` )
+    ///
+    /// When enabled, `Request::ip` is the original client's address taken from the header,
+    /// and `Request::proxy` gives the header. A connection without a valid header is closed,
+    /// so enable this only when all the connections come via such a proxy.
+    pub proxy_protocol:      bool,
+
+    /// Called on an I/O error of a connection, like a client going away
+    /// in the middle of a request or response ( default: `None` )
+    ///
+    /// The connection is closed after the error in any case.
+    pub on_connection_error: Option<OnConnectionError>,
+
+    #[cfg(feature="tls")]
+    /// TLS settings to serve `https://` and `wss://` ( default: `None` )
+    pub tls:                 Option<TlsConfig>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            keepalive_timeout:   Duration::from_secs(env::OHKAMI_KEEPALIVE_TIMEOUT()),
+            header_read_timeout: Duration::from_secs(30),
+            body_read_timeout:   Duration::from_secs(30),
+            #[cfg(feature="ws")]
+            websocket_timeout:   Duration::from_secs(env::OHKAMI_WEBSOCKET_TIMEOUT()),
+            max_header_size:     crate::request::HEADER_SIZE_LIMIT,
+            max_payload_size:    crate::request::PAYLOAD_LIMIT,
+            backlog:             1024,
+            max_connections:     None,
+            max_requests_in_flight: None,
+            retry_after:         Duration::from_secs(1),
+            shutdown_timeout:    Duration::from_secs(30),
+            proxy_protocol:      false,
+            on_connection_error: None,
+            #[cfg(feature="tls")]
+            tls:                 None,
+        }
+    }
+}
+
+/// `with_{field}` to set `{field}`, taking the inner value for an `Option` one
+macro_rules! with {
+    ($( $(#[$cfg:meta])* $with:ident : $field:ident = $($some:ident)? ($ty:ty), )*) => {
+        impl ServerConfig {$(
+            $(#[$cfg])*
+            #[doc = concat!("Set `", stringify!($field), "`")]
+            pub fn $with(mut self, $field: $ty) -> Self {
+                self.$field = $($some)?($field);
+                self
+            }
+        )*}
+    };
+} with! {
+    with_keepalive_timeout:      keepalive_timeout      = (Duration),
+    with_header_read_timeout:    header_read_timeout    = (Duration),
+    with_body_read_timeout:      body_read_timeout      = (Duration),
+    #[cfg(feature="ws")]
+    with_websocket_timeout:      websocket_timeout      = (Duration),
+    with_max_header_size:        max_header_size        = (usize),
+    with_max_payload_size:       max_payload_size       = (usize),
+    with_backlog:                backlog                = (u32),
+    with_max_connections:        max_connections        = Some(usize),
+    with_max_requests_in_flight: max_requests_in_flight = Some(usize),
+    with_retry_after:            retry_after            = (Duration),
+    with_shutdown_timeout:       shutdown_timeout       = (Duration),
+    with_proxy_protocol:         proxy_protocol         = (bool),
+    with_on_connection_error:    on_connection_error    = Some(OnConnectionError),
+    #[cfg(feature="tls")]
+    with_tls:                    tls                    = Some(TlsConfig),
+}
+
+impl ServerConfig {
+    pub(crate) fn report(&self, phase: ConnectionPhase, ip: Option<IpAddr>, error: io::Error) {
+        crate::DEBUG!("[{phase:?}] connection error from {ip:?}: {error}");
+        if let Some(OnConnectionError(hook)) = &self.on_connection_error {
+            hook(&ConnectionError { phase, ip, error })
+        }
+    }
+}
+
+/// # I/O error of a connection
+///
+/// Reported to `ServerConfig::on_connection_error`,
+/// for example, to count them in metrics.
+#[derive(Debug)]
+pub struct ConnectionError {
+    pub phase: ConnectionPhase,
+    /// Peer's IP address, `None` for a Unix domain socket or on `Accept`
+    pub ip:    Option<IpAddr>,
+    pub error: io::Error,
+}
+
+/// What the server was doing on the connection when an I/O error occurred
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionPhase {
+    /// Accepting a new connection
+    Accept,
+    /// TLS or HTTP/2 handshake
+    Handshake,
+    /// Reading a request
+    Read,
+    /// Writing a response
+    Write,
+    /// Running a WebSocket session
+    WebSocket,
+}
+
+/// # Hook for `ServerConfig::on_connection_error`
+///
+/// <br>
+///
+/// *example.rs*
+/// ```no_run
+/// use ohkami::prelude::*;
+/// use ohkami::{ServerConfig, OnConnectionError};
+/// use std::sync::atomic::{AtomicU64, Ordering};
+///
+/// static CONNECTION_ERRORS: AtomicU64 = AtomicU64::new(0);
+///
+/// #[tokio::main]
+/// async fn main() -> std::io::Result<()> {
+///     Ohkami::new((
+///         "/".GET(|| async {"Hello!"}),
+///     )).howl_with("0.0.0.0:3000", ServerConfig::default()
+///         .with_on_connection_error(OnConnectionError::new(|e| {
+///             eprintln!("[{:?}] {}", e.phase, e.error);
+///             CONNECTION_ERRORS.fetch_add(1, Ordering::Relaxed);
+///         }))
+///     ).await
+/// }
+/// ```
+#[derive(Clone)]
+pub struct OnConnectionError(Arc<dyn Fn(&ConnectionError) + Send + Sync>);
+
+impl OnConnectionError {
+    pub fn new(hook: impl Fn(&ConnectionError) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+}
+
+impl std::fmt::Debug for OnConnectionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("OnConnectionError")
+    }
+}
+
+#[cfg(feature="tls")]
+/// # TLS configuration for `ServerConfig::tls`
+///
+/// <br>
+///
+/// *example.rs*
+/// ```no_run
+/// use ohkami::prelude::*;
+/// use ohkami::{ServerConfig, TlsConfig};
+///
+/// #[tokio::main]
+/// async fn main() -> std::io::Result<()> {
+///     Ohkami::new((
+///         "/".GET(|| async {"Hello, TLS!"}),
+///     )).howl_with("0.0.0.0:443", ServerConfig::default()
+///         .with_tls(TlsConfig::from_pem_files("cert.pem", "key.pem")?)
+///     ).await
+/// }
+/// ```
+#[derive(Clone)]
+pub struct TlsConfig(pub(crate) std::sync::Arc<::rustls::ServerConfig>);
+
+#[cfg(feature="tls")]
+impl TlsConfig {
+    /// Load a certificate chain and a private key from PEM files
+    pub fn from_pem_files(
+        cert_path: impl AsRef<std::path::Path>,
+        key_path:  impl AsRef<std::path::Path>,
+    ) -> std::io::Result<Self> {
+        use ::rustls::pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject};
+
+        fn invalid_data(e: impl std::error::Error + Send + Sync + 'static) -> std::io::Error {
+            std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+        }
+
+        let certs = CertificateDer::pem_file_iter(cert_path).map_err(invalid_data)?
+            .collect::<Result<Vec<_>, _>>().map_err(invalid_data)?;
+        let key = PrivateKeyDer::from_pem_file(key_path).map_err(invalid_data)?;
+
+        let config = ::rustls::ServerConfig::builder_with_provider(
+            std::sync::Arc::new(::rustls::crypto::ring::default_provider())
+        )
+            .with_safe_default_protocol_versions().map_err(invalid_data)?
+            .with_no_client_auth()
+            .with_single_cert(certs, key).map_err(invalid_data)?;
+
+        Ok(Self::from_rustls(config))
+    }
+
+    /// Use a `rustls::ServerConfig` built by yourself.
+    /// 
+    /// ALPN protocols are set to `http/1.1` ( and `h2` with `"http2"` feature ) if not specified.
+    pub fn from_rustls(mut config: ::rustls::ServerConfig) -> Self {
+        if config.alpn_protocols.is_empty() {
+            config.alpn_protocols = vec![
+                #[cfg(feature="http2")]
+                b"h2".to_vec(),
+                b"http/1.1".to_vec(),
+            ];
+        }
+        Self(std::sync::Arc::new(config))
+    }
+}
+
+#[cfg(feature="tls")]
+impl std::fmt::Debug for TlsConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TlsConfig")
+            .field("alpn_protocols", &self.0.alpn_protocols.iter()
+                .map(|p| String::from_utf8_lossy(p))
+                .collect::<Vec<_>>())
+            .finish_non_exhaustive()
+    }
+}
+
+mod env {
+    #![allow(unused, non_snake_case)]
+
+    use std::sync::OnceLock;
+
+    pub(super) fn OHKAMI_KEEPALIVE_TIMEOUT() -> u64 {
+        static OHKAMI_KEEPALIVE_TIMEOUT: OnceLock<u64> = OnceLock::new();
+        *OHKAMI_KEEPALIVE_TIMEOUT.get_or_init(|| {
+            std::env::var("OHKAMI_KEEPALIVE_TIMEOUT").ok()
+                .map(|v| v.parse().ok()).flatten()
+                .unwrap_or(42)
+        })
+    }
+
+    #[cfg(feature="ws")]
+    pub(super) fn OHKAMI_WEBSOCKET_TIMEOUT() -> u64 {
+        static OHKAMI_WEBSOCKET_TIMEOUT: OnceLock<u64> = OnceLock::new();
+        *OHKAMI_WEBSOCKET_TIMEOUT.get_or_init(|| {
+            std::env::var("OHKAMI_WEBSOCKET_TIMEOUT").ok()
+                .map(|v| v.parse().ok()).flatten()
+                .unwrap_or(1 * 60 * 60)
+        })
+    }
+}
@@ -0,0 +1,49 @@
+#![cfg(feature="__rt_native__")]
+
+use std::sync::Arc;
+use ohkami_lib::signal::watch;
+
+
+/// Counter of running connections or requests up to `max`,
+/// working on any runtime
+pub(crate) struct Limit {
+    max:   usize,
+    count: watch::Sender<usize>,
+}
+
+/// A slot of `Limit`, released on drop
+pub(crate) struct Permit(Arc<Limit>);
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.0.count.send_modify(|count| *count -= 1);
+    }
+}
+
+impl Limit {
+    pub(crate) fn new(max: usize) -> Arc<Self> {
+        Arc::new(Self { max, count: watch::Sender::new(0) })
+    }
+
+    /// `None` if `max` slots are already taken
+    pub(crate) fn try_acquire(self: &Arc<Self>) -> Option<Permit> {
+        self.count.send_if_modified(|count| {
+            let vacant = *count < self.max;
+            if vacant {*count += 1}
+            vacant
+        }).then(|| Permit(self.clone()))
+    }
+
+    /// Wait for a slot to be released if `max` slots are already taken
+    pub(crate) async fn acquire(self: &Arc<Self>) -> Permit {
+        /* subscribing before `try_acquire` not to miss a release between them */
+        let mut released = self.count.subscribe();
+        loop {
+            if let Some(permit) = self.try_acquire() {
+                return permit
+            }
+            /* `self.count` is alive here, so this never fails */
+            let _ = released.changed().await;
+        }
+    }
+}
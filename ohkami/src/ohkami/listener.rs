@@ -0,0 +1,415 @@
+#![cfg(feature="__rt_native__")]
+
+use std::{io, net::IpAddr};
+use crate::__rt__;
+use crate::session::Connection;
+
+
+/// # Already-bound listener
+///
+/// Passed to `Ohkami::howl_on` to serve on a socket bound outside of Ohkami,
+/// like a privileged port bound before dropping privileges, one inherited
+/// by systemd socket activation ( `Listener::systemd` ), or one bound to
+/// port 0 in tests to read the actual port before starting the server.
+///
+/// Created from `std::net::TcpListener` or `std::os::unix::net::UnixListener`
+/// by `From`.
+///
+/// <br>
+///
+/// *example.rs*
+/// ```no_run
+/// use ohkami::prelude::*;
+/// use ohkami::{Listener, ServerConfig};
+///
+/// #[tokio::main]
+/// async fn main() -> std::io::Result<()> {
+///     let listener = std::net::TcpListener::bind("localhost:0")?;
+///     println!("listening on {}", listener.local_addr()?);
+///
+///     Ohkami::new((
+///         "/".GET(|| async {"Hello, ohkami!"}),
+///     )).howl_on([listener], ServerConfig::default(), async {
+///         tokio::signal::ctrl_c().await.unwrap();
+///     }).await
+/// }
+/// ```
+pub enum Listener {
+    Tcp(std::net::TcpListener),
+
+    #[cfg(unix)]
+    Unix(std::os::unix::net::UnixListener),
+}
+
+impl From<std::net::TcpListener> for Listener {
+    fn from(tcp: std::net::TcpListener) -> Self {
+        Self::Tcp(tcp)
+    }
+}
+
+#[cfg(unix)]
+impl From<std::os::unix::net::UnixListener> for Listener {
+    fn from(unix: std::os::unix::net::UnixListener) -> Self {
+        Self::Unix(unix)
+    }
+}
+
+#[cfg(unix)]
+impl Listener {
+    /// Take the listeners passed by systemd socket activation
+    /// ( `LISTEN_FDS` and `LISTEN_PID` ), in the order of the `.socket` unit.
+    ///
+    /// Returns an empty `Vec` when the process is not socket-activated.
+    /// The file descriptors are taken only once, so the second call also
+    /// returns an empty `Vec`.
+    ///
+    /// *example.rs*
+    /// ```no_run
+    /// use ohkami::prelude::*;
+    /// use ohkami::{Listener, ServerConfig};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> std::io::Result<()> {
+    ///     Ohkami::new((
+    ///         "/".GET(|| async {"Hello, systemd!"}),
+    ///     )).howl_on(Listener::systemd()?, ServerConfig::default(), async {
+    ///         tokio::signal::ctrl_c().await.unwrap();
+    ///     }).await
+    /// }
+    /// ```
+    pub fn systemd() -> io::Result<Vec<Self>> {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::os::fd::{FromRawFd, RawFd};
+        use ::socket2::{Socket, Domain, Type};
+
+        /// `SD_LISTEN_FDS_START`
+        const LISTEN_FDS_START: RawFd = 3;
+        static TAKEN: AtomicBool = AtomicBool::new(false);
+
+        let for_this_process = std::env::var("LISTEN_PID").ok()
+            .and_then(|pid| pid.parse::<u32>().ok())
+            .is_some_and(|pid| pid == std::process::id());
+        let Some(n_fds) = std::env::var("LISTEN_FDS").ok()
+            .and_then(|n| n.parse::<RawFd>().ok())
+            .filter(|_| for_this_process) else {
+            return Ok(Vec::new())
+        };
+
+        if TAKEN.swap(true, Ordering::AcqRel) {
+            return Ok(Vec::new())
+        }
+
+        (LISTEN_FDS_START..LISTEN_FDS_START + n_fds).map(|fd| {
+            // SAFETY: `fd` is passed to this process by systemd and taken only here
+            let socket = unsafe {Socket::from_raw_fd(fd)};
+            socket.set_cloexec(true)?;
+
+            if socket.r#type()? != Type::STREAM {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("fd {fd} passed by systemd is not a stream socket")
+                ))
+            }
+            match socket.local_addr()?.domain() {
+                Domain::IPV4 | Domain::IPV6 => Ok(Self::Tcp(socket.into())),
+                Domain::UNIX                => Ok(Self::Unix(socket.into())),
+                _ => Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("fd {fd} passed by systemd is neither TCP nor Unix domain socket")
+                )),
+            }
+        }).collect()
+    }
+}
+
+impl Listener {
+    pub(super) fn into_async(self) -> io::Result<AsyncListener> {
+        match self {
+            Self::Tcp(tcp) => {
+                let addr = LocalAddr::Tcp(tcp.local_addr()?);
+
+                #[cfg(feature="rt_glommio")] {
+                    use std::os::fd::{FromRawFd, IntoRawFd};
+                    Ok(AsyncListener::Tcp(unsafe {__rt__::TcpListener::from_raw_fd(tcp.into_raw_fd())}, addr))
+                }
+                #[cfg(not(feature="rt_glommio"))] {
+                    tcp.set_nonblocking(true)?;
+
+                    #[cfg(feature="rt_tokio")]
+                    return __rt__::TcpListener::from_std(tcp).map(|tcp| AsyncListener::Tcp(tcp, addr));
+                    #[cfg(feature="rt_async-std")]
+                    return Ok(AsyncListener::Tcp(__rt__::TcpListener::from(tcp), addr));
+                    #[cfg(feature="rt_smol")]
+                    return __rt__::TcpListener::try_from(tcp).map(|tcp| AsyncListener::Tcp(tcp, addr));
+                }
+            }
+
+            #[cfg(unix)]
+            Self::Unix(unix) => {
+                #[cfg(feature="rt_glommio")] {
+                    let _ = unix;
+                    Err(io::Error::new(
+                        io::ErrorKind::Unsupported,
+                        "serving on a pre-bound Unix domain socket is not supported on glommio"
+                    ))
+                }
+                #[cfg(not(feature="rt_glommio"))] {
+                    let addr = LocalAddr::Unix(unix.local_addr()?.as_pathname().map(ToOwned::to_owned));
+
+                    unix.set_nonblocking(true)?;
+
+                    #[cfg(feature="rt_tokio")]
+                    return __rt__::UnixListener::from_std(unix).map(|unix| AsyncListener::Unix(unix, addr));
+                    #[cfg(feature="rt_async-std")]
+                    return Ok(AsyncListener::Unix(__rt__::UnixListener::from(unix), addr));
+                    #[cfg(feature="rt_smol")]
+                    return __rt__::UnixListener::try_from(unix).map(|unix| AsyncListener::Unix(unix, addr));
+                }
+            }
+        }
+    }
+}
+
+
+/// # Listener a request arrived on
+///
+/// Returned by `Request::listener`, telling which of the addresses
+/// or listeners passed to `howl`s has accepted the connection.
+///
+/// <br>
+///
+/// *example.rs*
+/// ```no_run
+/// use ohkami::prelude::*;
+/// use ohkami::LocalAddr;
+///
+/// async fn metrics(req: &Request) -> Result<String, Response> {
+///     match req.listener().map(|l| l.local_addr()) {
+///         Some(LocalAddr::Tcp(addr)) if addr.port() == 9090 => Ok(format!("...")),
+///         _ => Err(Response::NotFound()),
+///     }
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     Ohkami::new((
+///         "/".GET(|| async {"Hello, ohkami!"}),
+///         "/metrics".GET(metrics),
+///     )).howl(["0.0.0.0:8080", "127.0.0.1:9090"]).await
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ListenerInfo {
+    index: usize,
+    addr:  LocalAddr,
+}
+
+impl ListenerInfo {
+    /// Position in the addresses or listeners passed to `howl`s
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Local address the listener is bound to
+    pub fn local_addr(&self) -> &LocalAddr {
+        &self.addr
+    }
+}
+
+/// Local address of a listener
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LocalAddr {
+    Tcp(std::net::SocketAddr),
+
+    /// `None` for an unnamed socket
+    #[cfg(unix)]
+    Unix(Option<std::path::PathBuf>),
+}
+
+
+/// Listener `howl`s accept connections from
+pub(super) enum AsyncListener {
+    Tcp(__rt__::TcpListener, LocalAddr),
+
+    #[cfg(unix)]
+    Unix(__rt__::UnixListener, LocalAddr),
+}
+
+impl AsyncListener {
+    /// Accept a connection with the peer's IP address, `None` for a Unix domain socket
+    pub(super) async fn accept(&self) -> io::Result<(Connection, Option<IpAddr>)> {
+        match self {
+            #[cfg(not(feature="rt_glommio"))]
+            Self::Tcp(tcp, _) => {
+                let (connection, addr) = tcp.accept().await?;
+                Ok((Connection::Tcp(connection), Some(addr.ip())))
+            }
+            #[cfg(feature="rt_glommio")]
+            Self::Tcp(tcp, _) => {
+                let connection = tcp.accept().await.map_err(io::Error::from)?;
+                let addr = connection.peer_addr().map_err(io::Error::from)?;
+                Ok((Connection::Tcp(connection), Some(addr.ip())))
+            }
+
+            #[cfg(all(unix, not(feature="rt_glommio")))]
+            Self::Unix(unix, _) => {
+                let (connection, _) = unix.accept().await?;
+                Ok((Connection::Unix(connection), None))
+            }
+            #[cfg(all(unix, feature="rt_glommio"))]
+            Self::Unix(unix, _) => {
+                let connection = unix.accept().await.map_err(io::Error::from)?;
+                Ok((Connection::Unix(connection), None))
+            }
+        }
+    }
+
+    /// `ListenerInfo` of this listener as the `index`-th one
+    pub(super) fn info(&self, index: usize) -> ListenerInfo {
+        let addr = match self {
+            Self::Tcp(_, addr) => addr.clone(),
+            #[cfg(unix)]
+            Self::Unix(_, addr) => addr.clone(),
+        };
+        ListenerInfo { index, addr }
+    }
+}
+
+/// # Address(es) to `howl` at
+///
+/// An address is
+///
+/// - `&str` or `String` like `"localhost:3000"`, `"0.0.0.0:80"`, `"[::]:80"`
+/// - `SocketAddr`, `SocketAddrV4` or `SocketAddrV6`
+/// - `(IpAddr, u16)`, `(Ipv4Addr, u16)`, `(Ipv6Addr, u16)` or `(&str, u16)`
+///
+/// and `Addresses` is one of them, or an array, `Vec` or slice of them.
+/// Each address is bound as a listener, all sharing the routes.
+pub trait Addresses {
+    #[doc(hidden)]
+    type Address: __rt__::ToSocketAddrs;
+    #[doc(hidden)]
+    fn into_addresses(self) -> Vec<Self::Address>;
+}
+
+mod sealed {
+    pub trait Address: crate::__rt__::ToSocketAddrs {}
+}
+
+macro_rules! Address {
+    ($( $t:ty ),*) => {$(
+        impl sealed::Address for $t {}
+        impl Addresses for $t {
+            type Address = Self;
+            fn into_addresses(self) -> Vec<Self> {
+                vec![self]
+            }
+        }
+    )*};
+} Address! {
+    &str, String,
+    std::net::SocketAddr, std::net::SocketAddrV4, std::net::SocketAddrV6,
+    (IpAddr, u16), (std::net::Ipv4Addr, u16), (std::net::Ipv6Addr, u16), (&str, u16)
+}
+
+impl<A: sealed::Address, const N: usize> Addresses for [A; N] {
+    type Address = A;
+    fn into_addresses(self) -> Vec<A> {
+        self.into()
+    }
+}
+impl<A: sealed::Address> Addresses for Vec<A> {
+    type Address = A;
+    fn into_addresses(self) -> Vec<A> {
+        self
+    }
+}
+impl<A: sealed::Address + Clone> Addresses for &[A] {
+    type Address = A;
+    fn into_addresses(self) -> Vec<A> {
+        self.to_vec()
+    }
+}
+
+pub(super) async fn bind(
+    address: impl __rt__::ToSocketAddrs,
+    backlog: u32,
+) -> std::io::Result<AsyncListener> {
+    #[cfg(feature="rt_tokio")]
+    let addrs = ::tokio::net::lookup_host(address).await?;
+    #[cfg(feature="rt_async-std")]
+    let addrs = address.to_socket_addrs().await?;
+    #[cfg(feature="rt_smol")]
+    let addrs = ::smol::net::resolve(address).await?;
+    #[cfg(feature="rt_glommio")]
+    let addrs = address.to_socket_addrs()?;
+
+    let mut last_err = None;
+    for addr in addrs {
+        match listen(addr, backlog) {
+            Ok(listener) => return Ok(listener),
+            Err(e) => last_err = Some(e),
+        }
+    }
+    Err(last_err.unwrap_or_else(|| std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        "could not resolve to any address"
+    )))
+}
+
+fn listen(
+    addr:    std::net::SocketAddr,
+    backlog: u32,
+) -> std::io::Result<AsyncListener> {
+    use ::socket2::{Socket, Domain, Type, Protocol};
+
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+    #[cfg(unix)] {
+        socket.set_reuse_address(true)?;
+    }
+    #[cfg(feature="rt_glommio")] {
+        /* same as `glommio::net::TcpListener::bind` for multiple executors */
+        socket.set_reuse_port(true)?;
+    }
+    socket.bind(&addr.into())?;
+    socket.listen(backlog.min(i32::MAX as u32) as i32)?;
+
+    Listener::Tcp(socket.into()).into_async()
+}
+
+#[cfg(unix)]
+/// Bind a Unix domain socket at `path`, replacing a stale socket file
+/// left by a previous process if any
+pub(super) fn bind_unix(
+    path:    &std::path::Path,
+    backlog: u32,
+) -> io::Result<AsyncListener> {
+    use std::os::unix::fs::FileTypeExt;
+
+    if std::fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_socket()) {
+        match std::os::unix::net::UnixStream::connect(path) {
+            Ok(_) => return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("{} is in use by another process", path.display())
+            )),
+            Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => std::fs::remove_file(path)?,
+            Err(e) => return Err(e),
+        }
+    }
+
+    #[cfg(feature="rt_glommio")] {
+        let _ = backlog;
+        __rt__::UnixListener::bind(path)
+            .map(|unix| AsyncListener::Unix(unix, LocalAddr::Unix(Some(path.to_owned()))))
+            .map_err(io::Error::from)
+    }
+    #[cfg(not(feature="rt_glommio"))] {
+        use ::socket2::{Socket, Domain, Type, SockAddr};
+
+        let socket = Socket::new(Domain::UNIX, Type::STREAM, None)?;
+        socket.bind(&SockAddr::unix(path)?)?;
+        socket.listen(backlog.min(i32::MAX as u32) as i32)?;
+
+        Listener::Unix(socket.into()).into_async()
+    }
+}
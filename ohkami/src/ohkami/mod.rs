@@ -6,14 +6,29 @@ mod _test;
 pub(crate) mod build;
 pub(crate) mod router;
 
+mod config;
+mod shutdown;
+mod listener;
+pub(crate) mod limit;
+pub(crate) mod panic;
+
 pub use build::{Route, Routes};
+pub use panic::PanicInfo;
+#[cfg(feature="__rt_native__")]
+pub use config::{ServerConfig, ConnectionError, ConnectionPhase, OnConnectionError};
+#[cfg(feature="__rt_native__")]
+pub use shutdown::Shutdown;
+#[cfg(feature="__rt_native__")]
+pub use listener::{Listener, ListenerInfo, LocalAddr, Addresses};
+#[cfg(all(feature="tls", feature="__rt_native__"))]
+pub use config::TlsConfig;
 
-use crate::fang::Fangs;
+use crate::fang::{Fangs, IntoHandler};
 use std::sync::Arc;
 use router::TrieRouter;
 
 #[cfg(feature="__rt_native__")]
-use {crate::{__rt__, Session}, ohkami_lib::signal};
+use {crate::{__rt__, Session}, ohkami_lib::signal, listener::AsyncListener, limit::Limit};
 
 /// # Ohkami - a robust wolf who serves your web app
 /// 
@@ -108,7 +123,22 @@ use {crate::{__rt__, Session}, ohkami_lib::signal};
 /// #### path_params：
 /// A tuple of types that implement `FromParam` trait.\
 /// If the path contains only one parameter, then you can omit the tuple.\
-/// (In current ohkami, at most *2* path params can be handled.)
+/// A route can have any number of path params, and a tuple can take up to *8*
+/// of them ( `Request::path.params()` iterates all ).
+/// A handler taking more params than its route has responds `500 Internal Server Error`.
+/// 
+/// The router also keeps the names of params : `format::Path` deserializes them into
+/// a struct by name, and `Request::path.param("id")` gets one of them.
+/// 
+/// A param can have a constraint taking part in matching, an integer type like
+/// `/:id<u64>` or ASCII chars like `/:slug<[a-z0-9-]+>`. A request not satisfying it
+/// tries the other routes ( static ones first, then constrained params in the
+/// registration order, unconstrained params and wildcards ) instead of reaching `FromParam`.
+/// 
+/// The last section of a route can be a wildcard `*name` like `/files/*path`,
+/// taking all the rest of path ( non-empty, without the leading `/` ) as a param.
+/// Static and `:param` routes precede it, and a request matching none of them
+/// falls back to the nearest wildcard ( so `/*path` works as a catch-all ).
 /// 
 /// <br>
 /// 
@@ -228,18 +258,114 @@ impl Ohkami {
         }
     }
 
+    /// Respond by `hook` when a handler or a fang of this `Ohkami` panics,
+    /// instead of the default `500 Internal Server Error`.
+    /// 
+    /// `hook` takes the panic message and location, and the request
+    /// as it was when the panic occurred. This works the same in `testing`.
+    /// 
+    /// For nested `Ohkami`s, the innermost one having `on_panic` handles the panic.
+    /// 
+    /// *note* : This sets a panic hook to record the location, calling the current one
+    /// after that. A panic is never caught when `panic = "abort"` ( e.g. on Workers ).
+    /// 
+    /// <br>
+    /// 
+    /// ---
+    /// 
+    /// *example.rs*
+    /// ```no_run
+    /// use ohkami::prelude::*;
+    /// use ohkami::{PanicInfo, serde::Serialize, format::JSON};
+    /// 
+    /// #[derive(Serialize)]
+    /// struct ErrorBody {
+    ///     message:    String,
+    ///     request_id: Option<String>,
+    ///     location:   Option<String>,
+    /// }
+    /// 
+    /// fn on_panic(info: &PanicInfo, req: &Request) -> Response {
+    ///     Response::InternalServerError().with_json(ErrorBody {
+    ///         message:    String::from("internal server error"),
+    ///         request_id: req.headers.custom("X-Request-Id").map(String::from),
+    ///         location:   cfg!(debug_assertions).then(|| info.location().map(String::from)).flatten(),
+    ///     })
+    /// }
+    /// 
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     Ohkami::new((
+    ///         "/".GET(|| async {"Hello!"}),
+    ///     )).on_panic(on_panic).howl("localhost:3000").await
+    /// }
+    /// ```
+    pub fn on_panic(self,
+        hook: impl Fn(&PanicInfo, &crate::Request) -> crate::Response + Send + Sync + 'static
+    ) -> Self {
+        let Self { routes, fangs } = self;
+        Self {
+            routes,
+            fangs: Some(Arc::new(panic::OnPanic::new(hook, fangs))),
+        }
+    }
+
+    /// Respond by `handler` to a request matching none of the routes of this `Ohkami`,
+    /// instead of the default `404 Not Found`.
+    /// 
+    /// This passes through the fangs like the routes do. For nested `Ohkami`s, a request
+    /// under the route of the innermost one having `fallback` is handled by it.
+    /// 
+    /// *note* : `handler` is called without path params.
+    /// 
+    /// <br>
+    /// 
+    /// ---
+    /// 
+    /// *example.rs*
+    /// ```
+    /// use ohkami::prelude::*;
+    /// use ohkami::format::HTML;
+    /// 
+    /// #[derive(Serialize)]
+    /// struct ErrorBody {
+    ///     message: String,
+    /// }
+    /// 
+    /// async fn api_not_found(req: &Request) -> Response {
+    ///     Response::NotFound().with_json(ErrorBody {
+    ///         message: format!("no API for `{} {}`", req.method, req.path.str()),
+    ///     })
+    /// }
+    /// 
+    /// async fn not_found_page() -> Response {
+    ///     Response::NotFound().with_html("<h1>Page not found</h1>")
+    /// }
+    /// 
+    /// # let _ =
+    /// Ohkami::new((
+    ///     "/".GET(|| async {HTML("<h1>Hello!</h1>")}),
+    ///     "/api".By(Ohkami::new((
+    ///         "/health".GET(|| async {"OK"}),
+    ///     )).fallback(api_not_found)),
+    /// )).fallback(not_found_page)
+    /// # ;
+    /// ```
+    pub fn fallback<T>(mut self, handler: impl IntoHandler<T>) -> Self {
+        self.routes.set_fallback(handler.into_handler());
+        self
+    }
+
     #[cfg(feature="__rt_native__")]
     /// Start serving at `address`!
     /// 
-    /// `address` is：
+    /// `address` is an address like `"localhost:5000"`, or several addresses
+    /// like `["0.0.0.0:80", "[::]:80"]` to serve the same routes on all of them.
+    /// See `Addresses` for details, and `Request::listener` for which one
+    /// a request arrived on.
     /// 
-    /// - `tokio::net::ToSocketAddrs` if using `tokio`
-    /// - `async_std::net::ToSocketAddrs` if using `async-std`
-    /// - `smol::net::AsyncToSocketAddrs` if using `smol`
-    /// - `std::net::ToSocketAddrs` if using `glommio`
-    /// 
-    /// *note* : Keep-Alive timeout is 42 seconds and this is not
-    /// configureable by user (it'll be in future version...)
+    /// *note* : This uses the default `ServerConfig` and panics when failed to bind
+    /// the address. Use `howl_with` for configuring the server or handling the error.
     /// 
     /// <br>
     /// 
@@ -290,142 +416,299 @@ impl Ohkami {
     ///     }).unwrap().join_all();
     /// }
     /// ```
-    pub async fn howl(self, address: impl __rt__::ToSocketAddrs) {
-        let router = Arc::new(self.into_router().into_radix());
+    pub async fn howl(self, address: impl Addresses) {
+        if let Err(e) = self.howl_with(address, ServerConfig::default()).await {
+            panic!("Failed to bind TCP listener: {e}")
+        }
+    }
 
-        #[cfg(any(feature="rt_tokio",feature="rt_async-std",feature="rt_smol"))]
-        let listener = __rt__::TcpListener::bind(address).await.expect("Failed to bind TCP listener");
-        #[cfg(any(feature="rt_glommio"))]
-        let listener = __rt__::TcpListener::bind(address).expect("Failed to bind TCP listener");
-        
-        let (close_tx, close_rx) = signal::watch::channel(());
+    #[cfg(feature="__rt_native__")]
+    /// Start serving at `address` with the `config`!
+    /// 
+    /// `address` is the same as `howl`.
+    /// 
+    /// This returns `Err` when failed to bind the address,
+    /// and returns `Ok(())` after graceful shutdown.
+    /// 
+    /// <br>
+    /// 
+    /// ---
+    /// 
+    /// *example.rs*
+    /// ```no_run
+    /// use ohkami::prelude::*;
+    /// use ohkami::ServerConfig;
+    /// use std::time::Duration;
+    /// 
+    /// #[tokio::main]
+    /// async fn main() -> std::io::Result<()> {
+    ///     Ohkami::new((
+    ///         "/".GET(|| async {"Hello, ohkami!"}),
+    ///     )).howl_with("localhost:5000", ServerConfig::default()
+    ///         .with_keepalive_timeout(Duration::from_secs(10))
+    ///         .with_header_read_timeout(Duration::from_secs(5))
+    ///         .with_max_payload_size(8 << 20)
+    ///     ).await
+    /// }
+    /// ```
+    pub async fn howl_with(self,
+        address: impl Addresses,
+        config:  ServerConfig,
+    ) -> std::io::Result<()> {
+        self.howl_until(address, config, async {
+            signal::ctrl_c().await.expect("Something was wrong around Ctrl-C");
+            crate::DEBUG!("Recieved Ctrl-C");
+        }).await
+    }
 
-        let ctrl_c = signal::ctrl_c();
-        let (ctrl_c_tx, ctrl_c_rx) = signal::watch::channel(());
-
-        #[cfg(any(feature="rt_tokio",feature="rt_async-std"))]
-        __rt__::spawn(async {
-            ctrl_c.await.expect("Something was wrong around Ctrl-C");
-            drop(ctrl_c_rx);
-        });
-        #[cfg(any(feature="rt_smol",feature="rt_glommio"))]
-        __rt__::spawn(async {
-            ctrl_c.await.expect("Something was wrong around Ctrl-C");
-            drop(ctrl_c_rx);
-        }).detach();
-
-        #[cfg(feature="rt_tokio")] {
-            loop {
-                __rt__::select! {
-                    accept = listener.accept() => {
-                        let Ok((connection, addr)) = accept else {continue};
-
-                        let session = Session::new(
-                            router.clone(),
-                            connection,
-                            addr.ip()
-                        );
-
-                        let close_rx = close_rx.clone();
-                        __rt__::spawn(async {
-                            session.manage().await;
-                            drop(close_rx)
-                        });
-                    }
-                    _ = ctrl_c_tx.closed() => {
-                        crate::DEBUG!("Recieved Ctrl-C, trying graceful shutdown");
-                        drop(listener);
-                        break
-                    }
-                }
-            }
+    #[cfg(feature="__rt_native__")]
+    /// Start serving at `address` with the `config`
+    /// until the `shutdown` future is done, then shutdown gracefully.
+    /// 
+    /// `address` is the same as `howl`.
+    /// 
+    /// On shutdown, the server stops accepting connections, closes idle ones,
+    /// notifies handlers taking `Shutdown`, and waits running sessions to finish
+    /// for `ServerConfig::shutdown_timeout` before force-closing them.
+    /// 
+    /// This returns `Err` when failed to bind the address,
+    /// and returns `Ok(())` after shutdown.
+    /// 
+    /// <br>
+    /// 
+    /// ---
+    /// 
+    /// *example.rs*
+    /// ```no_run
+    /// use ohkami::prelude::*;
+    /// use ohkami::ServerConfig;
+    /// use tokio::signal::unix::{signal, SignalKind};
+    /// 
+    /// #[tokio::main]
+    /// async fn main() -> std::io::Result<()> {
+    ///     let mut sigterm = signal(SignalKind::terminate())?;
+    /// 
+    ///     Ohkami::new((
+    ///         "/".GET(|| async {"Hello, ohkami!"}),
+    ///     )).howl_until("0.0.0.0:5000", ServerConfig::default(), async move {
+    ///         sigterm.recv().await;
+    ///     }).await
+    /// }
+    /// ```
+    pub async fn howl_until(self,
+        address:  impl Addresses,
+        config:   ServerConfig,
+        shutdown: impl std::future::Future<Output = ()>,
+    ) -> std::io::Result<()> {
+        let mut listeners = Vec::new();
+        for address in address.into_addresses() {
+            listeners.push(listener::bind(address, config.backlog).await?);
+        }
+        if listeners.is_empty() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "no address to serve at"
+            ))
         }
+        self.serve(listeners, config, shutdown).await;
+        Ok(())
+    }
 
-        #[cfg(feature="rt_async-std")] {
-            loop {
-                __rt__::select! {
-                    accept = __rt__::FutureExt::fuse(listener.accept()) => {
-                        let Ok((connection, addr)) = accept else {continue};
-
-                        let session = Session::new(
-                            router.clone(),
-                            connection,
-                            addr.ip()
-                        );
-
-                        let close_rx = close_rx.clone();
-                        __rt__::spawn(async {
-                            session.manage().await;
-                            drop(close_rx)
-                        });
-                    }
-                    _ = __rt__::FutureExt::fuse(ctrl_c_tx.closed()) => {
-                        crate::DEBUG!("Recieved Ctrl-C, trying graceful shutdown");
-                        drop(listener);
-                        break
-                    }
-                }
-            }
+    #[cfg(all(unix, feature="__rt_native__"))]
+    /// Start serving at the Unix domain socket `path`!
+    /// 
+    /// A stale socket file at `path` left by a previous process is replaced,
+    /// and the socket file is removed after graceful shutdown.
+    /// 
+    /// `Request::ip` is `None` for requests via Unix domain socket.
+    /// 
+    /// *note* : This uses the default `ServerConfig`, shuts down on Ctrl-C, and panics
+    /// when failed to bind the path. Use `howl_unix_until` for configuring the server
+    /// or handling the error. TLS and HTTP/2 are not served via Unix domain socket.
+    /// 
+    /// <br>
+    /// 
+    /// ---
+    /// 
+    /// *example.rs*
+    /// ```no_run
+    /// use ohkami::prelude::*;
+    /// 
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     Ohkami::new((
+    ///         "/".GET(|| async {"Hello, Unix domain socket!"}),
+    ///     )).howl_unix("/tmp/ohkami.sock").await
+    /// }
+    /// ```
+    pub async fn howl_unix(self, path: impl AsRef<std::path::Path>) {
+        if let Err(e) = self.howl_unix_until(path, ServerConfig::default(), async {
+            signal::ctrl_c().await.expect("Something was wrong around Ctrl-C");
+        }).await {
+            panic!("Failed to bind Unix domain socket: {e}")
         }
-        
-        #[cfg(feature="rt_smol")] {
-            loop {
-                __rt__::select! {
-                    accept = __rt__::FutureExt::fuse(listener.accept()) => {
-                        let Ok((connection, addr)) = accept else {continue};
-
-                        let session = Session::new(
-                            router.clone(),
-                            connection,
-                            addr.ip()
-                        );
-
-                        let close_rx = close_rx.clone();
-                        __rt__::spawn(async {
-                            session.manage().await;
-                            drop(close_rx)
-                        }).detach();
-                    }
-                    _ = __rt__::FutureExt::fuse(ctrl_c_tx.closed()) => {
-                        crate::DEBUG!("Recieved Ctrl-C, trying graceful shutdown");
-                        drop(listener);
-                        break
-                    }
-                }
-            }
+    }
+
+    #[cfg(all(unix, feature="__rt_native__"))]
+    /// Start serving at the Unix domain socket `path` with the `config`
+    /// until the `shutdown` future is done, then shutdown gracefully.
+    /// 
+    /// See `howl_unix` and `howl_until` for details.
+    pub async fn howl_unix_until(self,
+        path:     impl AsRef<std::path::Path>,
+        config:   ServerConfig,
+        shutdown: impl std::future::Future<Output = ()>,
+    ) -> std::io::Result<()> {
+        let path = path.as_ref();
+        let listener = listener::bind_unix(path, config.backlog)?;
+        self.serve(vec![listener], config, shutdown).await;
+        std::fs::remove_file(path)
+    }
+
+    #[cfg(feature="__rt_native__")]
+    /// Start serving on already-bound `listeners` with the `config`
+    /// until the `shutdown` future is done, then shutdown gracefully.
+    /// 
+    /// All the `listeners` share the same routes, and each of them
+    /// is accepted concurrently. See `Listener` for what can be passed,
+    /// `Request::listener` for which one a request arrived on,
+    /// and `howl_until` for details of the shutdown.
+    /// 
+    /// Unlike `howl_unix`, a socket file of a pre-bound Unix domain socket
+    /// is not removed after shutdown.
+    /// 
+    /// This returns `Err` when `listeners` is empty or failed to register
+    /// a listener to the runtime, and returns `Ok(())` after shutdown.
+    /// 
+    /// <br>
+    /// 
+    /// ---
+    /// 
+    /// *example.rs*
+    /// ```no_run
+    /// use ohkami::prelude::*;
+    /// use ohkami::ServerConfig;
+    /// use std::net::TcpListener;
+    /// 
+    /// #[tokio::main]
+    /// async fn main() -> std::io::Result<()> {
+    ///     let v4 = TcpListener::bind("0.0.0.0:80")?;
+    ///     let v6 = TcpListener::bind("[::]:80")?;
+    ///     /* drop privileges here */
+    /// 
+    ///     Ohkami::new((
+    ///         "/".GET(|| async {"Hello, ohkami!"}),
+    ///     )).howl_on([v4, v6], ServerConfig::default(), async {
+    ///         tokio::signal::ctrl_c().await.unwrap();
+    ///     }).await
+    /// }
+    /// ```
+    pub async fn howl_on(self,
+        listeners: impl IntoIterator<Item = impl Into<Listener>>,
+        config:    ServerConfig,
+        shutdown:  impl std::future::Future<Output = ()>,
+    ) -> std::io::Result<()> {
+        let listeners = listeners.into_iter()
+            .map(|listener| listener.into().into_async())
+            .collect::<std::io::Result<Vec<_>>>()?;
+        if listeners.is_empty() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "no listener to serve on"
+            ))
         }
+        self.serve(listeners, config, shutdown).await;
+        Ok(())
+    }
+
+    #[cfg(feature="__rt_native__")]
+    async fn serve(self,
+        listeners: Vec<AsyncListener>,
+        config:    ServerConfig,
+        shutdown:  impl std::future::Future<Output = ()>,
+    ) {
+        use crate::util::{until, timeout_in};
 
-        #[cfg(feature="rt_glommio")] {
-            loop {
-                __rt__::select! {
-                    accept = __rt__::FutureExt::fuse(listener.accept()) => {
-                        let Ok(connection) = accept else {continue};
-                        let Ok(addr) = connection.peer_addr() else {continue};
-        
-                        let session = Session::new(
-                            router.clone(),
-                            connection,
-                            addr.ip()
-                        );
-
-                        let close_rx = close_rx.clone();
-                        __rt__::spawn(async {
-                            session.manage().await;
-                            drop(close_rx)
-                        }).detach();
-                    }
-                    _ = __rt__::FutureExt::fuse(ctrl_c_tx.closed()) => {
-                        crate::DEBUG!("Recieved Ctrl-C, trying graceful shutdown");
-                        drop(listener);
-                        break
-                    }
+        let router = Arc::new(self.into_router().into_radix());
+        let config = Arc::new(config);
+
+        let (close_tx, close_rx) = signal::watch::channel(());
+        let (shutdown_tx, shutdown_rx) = Shutdown::channel();
+        let (abort_tx, abort_rx) = Shutdown::channel();
+
+        /* shared by all the listeners */
+        let connections = config.max_connections.map(Limit::new);
+        let in_flight = config.max_requests_in_flight.map(Limit::new);
+
+        for (index, listener) in listeners.into_iter().enumerate() {
+            let info = Arc::new(listener.info(index));
+            let (router, config) = (router.clone(), config.clone());
+            let (connections, in_flight) = (connections.clone(), in_flight.clone());
+            let (close_rx, shutdown_rx, abort_rx) = (close_rx.clone(), shutdown_rx.clone(), abort_rx.clone());
+
+            let accept_loop = async move {
+                while let Some((accept, permit)) = until(shutdown_rx.notified(), async {
+                    /* backpressure: not accepting until a connection is closed */
+                    let permit = match &connections {
+                        Some(connections) => Some(connections.acquire().await),
+                        None => None,
+                    };
+                    (listener.accept().await, permit)
+                }).await {
+                    let (connection, ip) = match accept {
+                        Ok(accepted) => accepted,
+                        Err(e) => {
+                            config.report(ConnectionPhase::Accept, None, e);
+                            continue
+                        }
+                    };
+
+                    let session = Session::new(
+                        router.clone(),
+                        config.clone(),
+                        connection,
+                        ip,
+                        info.clone(),
+                        shutdown_rx.clone(),
+                        in_flight.clone(),
+                    );
+
+                    let (close_rx, abort_rx) = (close_rx.clone(), abort_rx.clone());
+                    let session = async move {
+                        /* force-closed when `shutdown_timeout` is over */
+                        until(abort_rx.notified(), session.manage()).await;
+                        drop((permit, close_rx))
+                    };
+
+                    #[cfg(any(feature="rt_tokio",feature="rt_async-std"))]
+                    __rt__::spawn(session);
+                    #[cfg(any(feature="rt_smol",feature="rt_glommio"))]
+                    __rt__::spawn(session).detach();
                 }
-            }
+
+                /* stop listening before waiting sessions */
+                drop(listener);
+                drop(close_rx)
+            };
+
+            #[cfg(any(feature="rt_tokio",feature="rt_async-std"))]
+            __rt__::spawn(accept_loop);
+            #[cfg(any(feature="rt_smol",feature="rt_glommio"))]
+            __rt__::spawn(accept_loop).detach();
         }
 
-        crate::DEBUG!("Waiting {} session(s) to finish...", close_tx.receiver_count());
+        shutdown.await;
+
+        crate::DEBUG!("Trying graceful shutdown");
+        shutdown_tx.send_replace(true);
+
+        crate::DEBUG!("Waiting {} session(s) to finish...", close_tx.receiver_count() - 1);
         drop(close_rx);
-        close_tx.closed().await;
+        if timeout_in(config.shutdown_timeout, close_tx.closed()).await.is_none() {
+            crate::warning!("[WARNING] Force-closing {} session(s) still running after `shutdown_timeout`", close_tx.receiver_count());
+            abort_tx.send_replace(true);
+            close_tx.closed().await;
+        }
     }
 
     #[cfg(feature="rt_worker")]
@@ -0,0 +1,141 @@
+use std::{any::Any, cell::RefCell, future::Future, pin::Pin, sync::{Arc, Once}};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::task::Poll;
+use crate::fang::{BoxedFPC, Fangs, FangProc};
+use crate::{Request, Response};
+
+
+/// # Panic in a handler or a fang
+///
+/// Passed to the hook of `Ohkami::on_panic`.
+#[derive(Debug)]
+pub struct PanicInfo {
+    message:  Option<String>,
+    location: Option<String>,
+}
+
+impl PanicInfo {
+    pub(crate) fn new(panic: Box<dyn Any + Send>) -> Self {
+        let message = match panic.downcast::<String>() {
+            Ok(message) => Some(*message),
+            Err(panic)  => panic.downcast_ref::<&str>().map(|message| message.to_string()),
+        };
+        let location = LOCATION.with_borrow_mut(Option::take);
+        Self { message, location }
+    }
+
+    /// The panic message, `None` if the payload is not a string
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    /// Where the panic occurred, like `src/main.rs:12:5`
+    ///
+    /// `None` if the panic hook has been replaced by `std::panic::set_hook`
+    /// after `Ohkami::on_panic` was called.
+    pub fn location(&self) -> Option<&str> {
+        self.location.as_deref()
+    }
+}
+
+impl std::fmt::Display for PanicInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.message().unwrap_or("Box<dyn Any>"))?;
+        if let Some(location) = self.location() {
+            write!(f, " at {location}")?;
+        }
+        Ok(())
+    }
+}
+
+thread_local! {
+    /// location of the last panic on this thread, set by the hook of `record_location`
+    static LOCATION: RefCell<Option<String>> = const {RefCell::new(None)};
+}
+
+/// Chain a panic hook recording the location for `PanicInfo`,
+/// keeping the current hook's behavior
+fn record_location() {
+    static RECORD_LOCATION: Once = Once::new();
+    RECORD_LOCATION.call_once(|| {
+        let current = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            LOCATION.set(info.location().map(ToString::to_string));
+            current(info)
+        }));
+    });
+}
+
+/// Catch a panic while polling `proc`, not only while creating it
+pub(crate) async fn catch_unwinding<F: Future>(
+    proc: F
+) -> Result<F::Output, Box<dyn Any + Send>> {
+    let mut proc = std::pin::pin!(proc);
+    std::future::poll_fn(|cx| match catch_unwind(AssertUnwindSafe(|| proc.as_mut().poll(cx))) {
+        Ok(poll)   => poll.map(Ok),
+        Err(panic) => Poll::Ready(Err(panic)),
+    }).await
+}
+
+type Hook = Arc<dyn Fn(&PanicInfo, &Request) -> Response + Send + Sync>;
+
+/// Fangs of `Ohkami::on_panic`, catching panics in `fangs` and the handlers inside
+pub(crate) struct OnPanic {
+    hook:  Hook,
+    fangs: Option<Arc<dyn Fangs>>,
+}
+
+impl OnPanic {
+    pub(crate) fn new(
+        hook:  impl Fn(&PanicInfo, &Request) -> Response + Send + Sync + 'static,
+        fangs: Option<Arc<dyn Fangs>>,
+    ) -> Self {
+        record_location();
+        Self { hook: Arc::new(hook), fangs }
+    }
+}
+
+impl Fangs for OnPanic {
+    fn build(&self, inner: BoxedFPC) -> BoxedFPC {
+        let inner = match &self.fangs {
+            Some(fangs) => fangs.build(inner),
+            None        => inner,
+        };
+        BoxedFPC::from_proc(OnPanicProc { hook: self.hook.clone(), inner })
+    }
+}
+
+struct OnPanicProc {
+    hook:  Hook,
+    inner: BoxedFPC,
+}
+
+impl FangProc for OnPanicProc {
+    #[cfg(not(feature="rt_worker"))]
+    fn bite<'b>(&'b self, req: &'b mut Request) -> impl Future<Output = Response> + Send {
+        self.bite_boxed(req)
+    }
+    #[cfg(feature="rt_worker")]
+    fn bite<'b>(&'b self, req: &'b mut Request) -> impl Future<Output = Response> {
+        self.bite_boxed(req)
+    }
+
+    #[cfg(not(feature="rt_worker"))]
+    fn bite_boxed<'b>(&'b self, req: &'b mut Request) -> Pin<Box<dyn Future<Output = Response> + Send + 'b>> {
+        Box::pin(async move {
+            match catch_unwinding(self.inner.bite_boxed(req)).await {
+                Ok(res)    => res,
+                Err(panic) => (self.hook)(&PanicInfo::new(panic), req),
+            }
+        })
+    }
+    #[cfg(feature="rt_worker")]
+    fn bite_boxed<'b>(&'b self, req: &'b mut Request) -> Pin<Box<dyn Future<Output = Response> + 'b>> {
+        Box::pin(async move {
+            match catch_unwinding(self.inner.bite_boxed(req)).await {
+                Ok(res)    => res,
+                Err(panic) => (self.hook)(&PanicInfo::new(panic), req),
+            }
+        })
+    }
+}
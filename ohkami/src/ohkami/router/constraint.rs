@@ -0,0 +1,120 @@
+/// Constraint on a path param like `<u64>` of `/:id<u64>`,
+/// taking part in route matching
+///
+/// - an integer type : `u8` ~ `u128`, `usize`, `i8` ~ `i128`, `isize`
+/// - a set of ASCII chars and its repetition : `[a-z0-9-]+`, `[0-9a-f]{8}`, ...
+///   ( the repetition is one of `+`, `*`, `{n}`, `{m,}`, `{m,n}`, and
+///   just `[...]` means a single char )
+///
+/// A param is checked as it is in the request, not percent-decoded.
+/// An integer is written in decimal digits, with `-` for a negative one
+/// but without `+`.
+#[derive(Clone, Copy)]
+pub(crate) struct Constraint {
+    source: &'static str,
+    kind:   Kind,
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Int(fn(&str) -> bool),
+    Chars {
+        /// `1 << b` is set for each allowed byte `b`
+        ascii: u128,
+        min:   usize,
+        max:   Option<usize>,
+    },
+}
+
+const _: () = {
+    impl PartialEq for Constraint {
+        fn eq(&self, other: &Self) -> bool {
+            self.source == other.source
+        }
+    }
+
+    impl std::fmt::Debug for Constraint {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "<{}>", self.source)
+        }
+    }
+};
+
+impl Constraint {
+    /// Parse `source`, the content of `<...>`
+    pub(crate) fn parse(source: &'static str) -> Result<Self, String> {
+        macro_rules! int {
+            ($($int:ty)*) => {$(
+                if source == stringify!($int) {
+                    return Ok(Self { source, kind: Kind::Int(|s| !s.starts_with('+') && s.parse::<$int>().is_ok()) })
+                }
+            )*};
+        }
+        int!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+
+        let Some(class) = source.strip_prefix('[') else {
+            return Err(format!("Unknown constraint `<{source}>`: expected an integer type or `[...]`"))
+        };
+        let Some((class, repetition)) = class.split_once(']') else {
+            return Err(format!("Unclosed `[` in constraint `<{source}>`"))
+        };
+
+        let mut ascii = 0_u128;
+        let mut class = class.chars().peekable();
+        while let Some(c) = class.next() {
+            let (start, end) = match (c, class.peek()) {
+                (start, Some('-')) => {
+                    let _/* '-' */ = class.next();
+                    match class.next() {
+                        Some(end) => (start, end),
+                        None      => {ascii |= 1 << b'-'; (start, start)}
+                    }
+                }
+                (c, _) => (c, c)
+            };
+            if !(start.is_ascii() && end.is_ascii()) || start > end {
+                return Err(format!("Invalid chars `{start}-{end}` in constraint `<{source}>`"))
+            }
+            for b in start as u8..=end as u8 {
+                ascii |= 1 << b;
+            }
+        }
+        if ascii == 0 {
+            return Err(format!("Empty `[]` in constraint `<{source}>`"))
+        }
+
+        let (min, max) = match repetition {
+            ""  => (1, Some(1)),
+            "+" => (1, None),
+            "*" => (0, None),
+            _ => {
+                let parse_n = |n: &str| n.trim().parse::<usize>()
+                    .map_err(|_| format!("Invalid repetition `{repetition}` in constraint `<{source}>`"));
+                match repetition.strip_prefix('{').and_then(|r| r.strip_suffix('}')) {
+                    None => return Err(format!("Invalid repetition `{repetition}` in constraint `<{source}>`")),
+                    Some(r) => match r.split_once(',') {
+                        None         => {let n = parse_n(r)?; (n, Some(n))}
+                        Some((m, "")) => (parse_n(m)?, None),
+                        Some((m, n))  => (parse_n(m)?, Some(parse_n(n)?)),
+                    }
+                }
+            }
+        };
+        if max.is_some_and(|max| max < min) {
+            return Err(format!("Invalid repetition `{repetition}` in constraint `<{source}>`"))
+        }
+
+        Ok(Self { source, kind: Kind::Chars { ascii, min, max } })
+    }
+
+    #[inline]
+    pub(crate) fn matches(&self, param: &[u8]) -> bool {
+        match self.kind {
+            Kind::Int(parse) => std::str::from_utf8(param).is_ok_and(parse),
+            Kind::Chars { ascii, min, max } => {
+                min <= param.len() && max.is_none_or(|max| param.len() <= max) &&
+                param.iter().all(|&b| b < 128 && ascii & (1 << b) != 0)
+            }
+        }
+    }
+}
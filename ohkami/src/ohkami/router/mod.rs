@@ -6,6 +6,9 @@ pub(crate) use trie::TrieRouter;
 mod radix;
 pub(crate) use radix::RadixRouter;
 
+mod constraint;
+use constraint::Constraint;
+
 use std::{collections::VecDeque, iter::Peekable, str::Chars};
 
 
@@ -35,6 +38,10 @@ impl RouteSections {
             sections.push_back(section)
         }
 
+        if sections.iter().rev().skip(1).any(RouteSection::is_wildcard) {
+            panic!("Wildcard must be the last section: `{route}`")
+        }
+
         Self { litreral:route, sections }
     }
 
@@ -53,11 +60,19 @@ const _: () = {
 #[derive(Clone)]
 pub enum RouteSection {
     Static(&'static [u8]),
-    Param,
+    /// `:name` or `:name<constraint>`
+    Param(&'static str, Option<Constraint>),
+    /// `*name`, matching all the rest of path
+    Wildcard(&'static str),
 }
 impl RouteSection {
+    pub(crate) fn is_wildcard(&self) -> bool {
+        matches!(self, Self::Wildcard(_))
+    }
+
     pub(crate) fn new(section_bytes: &'static [u8]) -> Result<Self, String> {
-        let mut section_chars = std::str::from_utf8(section_bytes).unwrap().chars().peekable();
+        let section_str = std::str::from_utf8(section_bytes).unwrap();
+        let mut section_chars = section_str.chars().peekable();
 
         fn validate_section_name(mut name: Peekable<Chars>) -> Result<(), String> {
             let is_invalid_head_or_tail_char = |c: char| !/* NOT */ matches!(c,
@@ -90,9 +105,20 @@ impl RouteSection {
         match section_chars.peek() {
             None => Err(format!("Found an empty route section_chars")),
             Some(':') => {
-                let _/* colon */ = section_chars.next();
+                let (name, constraint) = match section_str[1..].split_once('<') {
+                    None => (&section_str[1..], None),
+                    Some((name, constraint)) => match constraint.strip_suffix('>') {
+                        None             => return Err(String::from("Unclosed `<` in path section")),
+                        Some(constraint) => (name, Some(Constraint::parse(constraint)?)),
+                    }
+                };
+                let _/* validation */ = validate_section_name(name.chars().peekable())?;
+                Ok(Self::Param(name, constraint))
+            },
+            Some('*') => {
+                let _/* asterisk */ = section_chars.next();
                 let _/* validation */ = validate_section_name(section_chars)?;
-                Ok(Self::Param)
+                Ok(Self::Wildcard(&section_str[1..]))
             },
             _ => {
                 let _/* validation */ = validate_section_name(section_chars)?;
@@ -105,7 +131,9 @@ const _: () = {
     impl std::fmt::Debug for RouteSection {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             match self {
-                Self::Param         => f.write_str(":Param"),
+                Self::Param(name, None)    => write!(f, ":{name}"),
+                Self::Param(name, Some(c)) => write!(f, ":{name}{c:?}"),
+                Self::Wildcard(name)       => write!(f, "*{name}"),
                 Self::Static(bytes) => f.write_str(std::str::from_utf8(bytes).unwrap()),
             }
         }
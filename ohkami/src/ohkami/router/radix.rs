@@ -1,4 +1,5 @@
 use crate::request::Path;
+use super::Constraint;
 use crate::{Method, Request, Response};
 use crate::fang::{FangProcCaller, BoxedFPC};
 use ohkami_lib::Slice;
@@ -13,11 +14,16 @@ pub(crate) struct RadixRouter {
     pub(super) PATCH:   Node,
     pub(super) DELETE:  Node,
     pub(super) OPTIONS: Node,
+    pub(super) extensions: Box<[(crate::ExtensionMethod, Node)]>,
 }
 
 pub(super) struct Node {
     pub(super) patterns:  &'static [Pattern],
     pub(super) children:  &'static [Node],
+    /// whether a handler is registered here, not only passed through
+    pub(super) handles: bool,
+    /// whether `proc` takes the request body as a stream
+    pub(super) streams_payload: bool,
     pub(super) proc:      BoxedFPC,
     pub(super) __catch__: BoxedFPC,
 } const _: () = {
@@ -40,12 +46,15 @@ pub(super) struct Node {
 
 pub(super) enum Pattern {
     Static(&'static [u8]),
-    Param,
+    Param(&'static str, Option<Constraint>),
+    Wildcard(&'static str),
 } const _: () = {
     impl std::fmt::Debug for Pattern {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             match self {
-                Self::Param         => f.write_str(":Param"),
+                Self::Param(name, None)    => write!(f, ":{name}"),
+                Self::Param(name, Some(c)) => write!(f, ":{name}{c:?}"),
+                Self::Wildcard(name)       => write!(f, "*{name}"),
                 Self::Static(bytes) => {
                     f.write_char('\'')?;
                     f.write_str(std::str::from_utf8(bytes).unwrap())?;
@@ -66,87 +75,136 @@ impl RadixRouter {
         &self,
         req: &mut Request,
     ) -> Response {
-        (match req.method {
+        let (proc, _streams_payload) = (match req.method {
             Method::GET     => &self.GET,
             Method::PUT     => &self.PUT,
             Method::POST    => &self.POST,
             Method::PATCH   => &self.PATCH,
             Method::DELETE  => &self.DELETE,
             Method::OPTIONS => &self.OPTIONS,
-            Method::HEAD => {
-                let mut res = self.GET.search(&mut req.path).call_bite(req).await;
-                {/* not `res.drop_content()` to leave `Content-Type`, `Content-Length` */
-                    res.content = crate::response::Content::None;
-                }
+            Method::HEAD    => &self.GET,
+            Method::Extension(ext) => match self.extensions.iter().find(|(m, _)| *m == ext) {
+                Some((_, node)) => node,
+                None => return Response::NotImplemented()
+            }
+        }).search(&mut req.path);
+
+        /* a client expecting `100 Continue` waits for fangs and the handler to accept the body */
+        #[cfg(feature="__rt_native__")]
+        if !_streams_payload && !req.expects_continue() {
+            if let Err(res) = req.load_payload().await {
                 return res
             }
-        }).search(&mut req.path).call_bite(req).await
+        }
+
+        let mut res = proc.call_bite(req).await;
+        if req.method.isHEAD() {
+            /* not `res.drop_content()` to leave `Content-Type`, `Content-Length` */
+            res.content = crate::response::Content::None;
+        }
+        res
     }
 }
 
 impl Node {
     #[inline]
+    /// Returns the proc to call and whether it takes the request body as a stream
     pub(super/* for test */) fn search(&self,
         path: &mut Path
-    ) -> &dyn FangProcCaller {
+    ) -> (&BoxedFPC, bool) {
         // SAFETY:
         // 1. `req` must be alive while `search`
         // 2. `Request` DOESN'T have method that mutates `bytes`,
         //    So what `bytes` refers to is NEVER changed by any other process
         //    while `search`
-        let mut bytes = unsafe {path.normalized_bytes()};
-
-        let mut target = self;
+        let bytes = unsafe {path.normalized_bytes()};
 
         #[cfg(feature="DEBUG")]
         println!("[path] '{}'", bytes.escape_ascii());
 
-        loop {
-            #[cfg(feature="DEBUG")]
-            println!("[target] {:#?}", target);
+        match self.search_from(bytes, path) {
+            Ok(node) => {
+                #[cfg(feature="DEBUG")]
+                println!("Found: {node:?}");
+
+                (&node.proc, node.streams_payload)
+            }
+            Err(dead_end) => {
+                /* not to pass params of a partially matched route to the fallback */
+                path.truncate_params(0);
+                (&dead_end.__catch__, false)
+            }
+        }
+    }
+
+    /// Match `bytes` to the patterns of this node and then to the children,
+    /// backtracking to the next matchable child when the search under one fails.
+    /// So, for example, `/users/42/profile` reaches `/users/:name/profile`
+    /// even with `/users/:id<u64>/posts` taking `42` first,
+    /// and a wildcard, sorted last, catches what its siblings don't.
+    ///
+    /// Returns the dead end of the first way tried when not found.
+    fn search_from<'n>(&'n self,
+        mut bytes: &[u8],
+        path: &mut Path,
+    ) -> Result<&'n Node, &'n Node> {
+        #[cfg(feature="DEBUG")]
+        println!("[target] {self:#?}");
+        #[cfg(feature="DEBUG")]
+        println!("[patterns] {:?}", self.patterns);
+
+        for pattern in self.patterns {
+            if bytes.is_empty() || unsafe {bytes.get_unchecked(0)} != &b'/' {
+                // At least one `pattern` to match is remaining
+                // but remaining `bytes` doesn't start with '/'
+                return Err(self)
+            }
+
+            bytes = unsafe {bytes.get_unchecked(1..)};
+
             #[cfg(feature="DEBUG")]
-            println!("[patterns] {:?}", target.patterns);
-    
-            for pattern in target.patterns {
-                if bytes.is_empty() || unsafe {bytes.get_unchecked(0)} != &b'/' {
-                    // At least one `pattern` to match is remaining
-                    // but remaining `bytes` doesn't start with '/'
-                    return &target.__catch__
-                }
+            println!("[bytes striped prefix '/'] '{}'", bytes.escape_ascii());
 
-                bytes = unsafe {bytes.get_unchecked(1..)};
-                
-                #[cfg(feature="DEBUG")]
-                println!("[bytes striped prefix '/'] '{}'", bytes.escape_ascii());
-        
-                match pattern {
-                    Pattern::Static(s) => bytes = match bytes.strip_prefix(*s) {
-                        Some(remaining) => remaining,
-                        None            => return &target.__catch__,
-                    },
-                    Pattern::Param => {
-                        let (param, remaining) = split_next_section(bytes);
-                        unsafe {path.push_param(Slice::from_bytes(param))}
-                        bytes = remaining;
-                    },
+            match pattern {
+                Pattern::Static(s) => bytes = match bytes.strip_prefix(*s) {
+                    Some(remaining) if is_section_end(remaining) => remaining,
+                    _ => return Err(self),
+                },
+                Pattern::Param(name, constraint) => {
+                    let (param, remaining) = split_next_section(bytes);
+                    if constraint.is_some_and(|c| !c.matches(param)) {
+                        return Err(self)
+                    }
+                    unsafe {path.push_param(name, Slice::from_bytes(param))}
+                    bytes = remaining;
+                },
+                Pattern::Wildcard(name) => {
+                    if bytes.is_empty() {return Err(self)}
+                    unsafe {path.push_param(name, Slice::from_bytes(bytes))}
+                    bytes = &[];
                 }
             }
+        }
 
-            if bytes.is_empty() {
-                #[cfg(feature="DEBUG")]
-                println!("Found: {target:?}");
-        
-                return  &target.proc
-            } else {
-                #[cfg(feature="DEBUG")]
-                println!("not found, searching children: {:#?}", target.children);
-        
-                target = match target.matchable_child(bytes) {
-                    Some(child) => child,
-                    None        => return &target.__catch__,
+        if bytes.is_empty() {
+            return if self.handles {Ok(self)} else {Err(self)}
+        }
+
+        #[cfg(feature="DEBUG")]
+        println!("not found, searching children: {:#?}", self.children);
+
+        let n_params = path.n_params();
+        let mut dead_end = None;
+        for child in self.children.iter().filter(|c| c.is_matchable_to(bytes)) {
+            match child.search_from(bytes, path) {
+                Ok(found) => return Ok(found),
+                Err(d) => {
+                    path.truncate_params(n_params);
+                    dead_end.get_or_insert(d);
                 }
             }
         }
+        Err(dead_end.unwrap_or(self))
     }
 }
 
@@ -154,21 +212,18 @@ impl Node {
 /*===== utils =====*/
 
 impl Node {
-    #[inline] fn matchable_child(&self, path: &[u8]) -> Option<&Node> {
-        for child in &*self.children {
-            if child.patterns.first()?.is_matchable_to(path) {
-                return Some(child)
-            }
-        }
-        None
+    #[inline] fn is_matchable_to(&self, path: &[u8]) -> bool {
+        self.patterns.first().is_some_and(|p| p.is_matchable_to(path))
     }
 }
 
 impl Pattern {
     #[inline(always)] fn is_matchable_to(&self, path: &[u8]) -> bool {
         match self {
-            Self::Param     => true,
-            Self::Static(s) => (&path[1..]/* skip initial '/' */).starts_with(s),
+            Self::Param(_, None)    => true,
+            Self::Param(_, Some(c)) => c.matches(split_next_section(&path[1..]).0),
+            Self::Wildcard(_)       => path.len() > 1,
+            Self::Static(s)         => (&path[1..]/* skip initial '/' */).strip_prefix(*s).is_some_and(is_section_end),
         }
     }
 }
@@ -187,3 +242,9 @@ impl Pattern {
         }
     } (path, &[])
 }
+
+/// Whether `rest`, remaining after a section, is at the end of the section
+/// ( not to match `/apix` to `/api` )
+#[inline(always)] fn is_section_end(rest: &[u8]) -> bool {
+    rest.first().is_none_or(|b| *b == b'/')
+}
@@ -1,7 +1,8 @@
 use std::{borrow::Cow, sync::Arc};
-use super::{RouteSection, RouteSections};
+use super::{RouteSection, RouteSections, Constraint};
 use super::super::build::{Handlers, ByAnother};
 use crate::fang::{BoxedFPC, Fangs, Handler};
+use crate::{Method, ExtensionMethod};
 
 
 #[derive(Debug)]
@@ -14,6 +15,8 @@ pub struct TrieRouter {
     pub(super) PATCH:   Node,
     pub(super) DELETE:  Node,
     pub(super) OPTIONS: Node,
+    /// root nodes of extension methods registered by `.method(...)`
+    pub(super) extensions: Vec<(ExtensionMethod, Node)>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -40,6 +43,8 @@ pub(super) struct Node {
     /// Why Option: root node doesn't have pattern
     pub(super) pattern:    Option<Pattern>,
     pub(super) handler:    Option<Handler>,
+    /// set by `Ohkami::fallback` at the root of the `Ohkami`
+    pub(super) fallback:   Option<Handler>,
     pub(super) fangs_list: FangsList,
     pub(super) children:   Vec<Node>,
 } const _: () = {
@@ -48,6 +53,7 @@ pub(super) struct Node {
             f.debug_struct("")
                 .field("pattern",    &self.pattern)
                 .field("handler",    &self.handler.as_ref().map(|_| '#'))
+                .field("fallback",   &self.fallback.as_ref().map(|_| '#'))
                 .field("fangs_list", &self.fangs_list.iter().map(|_| '#').collect::<Vec<_>>())
                 .field("children",   &self.children)
                 .finish()
@@ -58,12 +64,15 @@ pub(super) struct Node {
 #[derive(Clone)]
 pub(super) enum Pattern {
     Static(Cow<'static, [u8]>),
-    Param,
+    Param(&'static str, Option<Constraint>),
+    Wildcard(&'static str),
 } const _: () = {
     impl std::fmt::Debug for Pattern {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             match self {
-                Self::Param     => f.write_str(":Param"),
+                Self::Param(name, None)    => write!(f, ":{name}"),
+                Self::Param(name, Some(c)) => write!(f, ":{name}{c:?}"),
+                Self::Wildcard(name)       => write!(f, "*{name}"),
                 Self::Static(v) => f.write_str(&format!(
                     "'{}'", std::str::from_utf8(&v).unwrap()
                 )),
@@ -74,8 +83,12 @@ pub(super) enum Pattern {
     impl PartialEq for Pattern {
         fn eq(&self, other: &Self) -> bool {
             match self {
-                Self::Param => match other {
-                    Self::Param => true,
+                Self::Param(this_name, this_constraint) => match other {
+                    Self::Param(other_name, other_constraint) => this_name == other_name && this_constraint == other_constraint,
+                    _ => false,
+                }
+                Self::Wildcard(this_name) => match other {
+                    Self::Wildcard(other_name) => this_name == other_name,
                     _ => false,
                 }
                 Self::Static(this_bytes) => {
@@ -91,7 +104,8 @@ pub(super) enum Pattern {
     impl From<RouteSection> for Pattern {
         fn from(section: RouteSection) -> Self {
             match section {
-                RouteSection::Param         => Pattern::Param,
+                RouteSection::Param(name, c) => Pattern::Param(name, c),
+                RouteSection::Wildcard(name) => Pattern::Wildcard(name),
                 RouteSection::Static(bytes) => Pattern::Static(Cow::Borrowed(bytes))
             }
         }
@@ -167,6 +181,7 @@ impl TrieRouter {
             PATCH:   Node::root(),
             DELETE:  Node::root(),
             OPTIONS: Node::root(),
+            extensions: Vec::new(),
         }
     }
 
@@ -175,7 +190,7 @@ impl TrieRouter {
     }
 
     pub(crate) fn register_handlers(&mut self, handlers: Handlers) {
-        let Handlers { route, GET, PUT, POST, PATCH, DELETE } = handlers;
+        let Handlers { route, GET, PUT, POST, PATCH, DELETE, extensions } = handlers;
 
         let methods = if !self.routes.insert(route.literal()) {
             panic!("Duplicate routes registration: `{}`", route.literal())
@@ -185,13 +200,16 @@ impl TrieRouter {
                     let mut methods = Vec::new();
                     $(
                         if $method.is_some() {
-                            methods.push(stringify!($method))
+                            methods.push(Method::$method)
                         }
                     )*
                     methods
                 }}
             } allow_methods! { GET, PUT, POST, PATCH, DELETE }
         };
+        let methods = methods.into_iter()
+            .chain(extensions.iter().map(|(ext, _)| Method::Extension(*ext)))
+            .collect::<Vec<_>>();
 
         macro_rules! register {
             ($( $method:ident ),*) => {$(
@@ -201,12 +219,16 @@ impl TrieRouter {
             )*};
         } register! { GET, PUT, POST, PATCH, DELETE }
 
+        for (ext, h) in extensions {
+            self.extension_mut(ext).register_handler(route.clone().into_iter(), h).expect("Failed to register handler");
+        }
+
         self.OPTIONS.register_handler(route.into_iter(), Handler::new(move |req| {
             let mut available_methods = methods.clone();
-            if available_methods.contains(&"GET") {
-                available_methods.push("HEAD")
+            if available_methods.contains(&Method::GET) {
+                available_methods.push(Method::HEAD)
             }
-            available_methods.push("OPTIONS");
+            available_methods.push(Method::OPTIONS);
 
             Box::pin(async move {
                 #[cfg(debug_assertions)] {
@@ -222,12 +244,13 @@ impl TrieRouter {
                             whitch is the default for a valid preflight request,
                             by a successful one in its proc.
                         */
-                        (if available_methods.contains(&method) {
+                        (if available_methods.iter().any(|m| m.as_str() == method) {
                             crate::Response::NotImplemented()
                         } else {
                             crate::Response::BadRequest()
                         }).with_headers(|h| h
-                            .AccessControlAllowMethods(available_methods.join(", "))
+                            .AccessControlAllowMethods(available_methods.iter()
+                                .map(Method::as_str).collect::<Vec<_>>().join(", "))
                         )
                     }
                     None => {
@@ -257,6 +280,22 @@ impl TrieRouter {
                 )*
             };
         } apply_to! { GET, PUT, POST, PATCH, DELETE, OPTIONS }
+
+        for (_, node) in &mut self.extensions {
+            node.apply_fangs(id.clone(), fangs.clone());
+        }
+    }
+
+    pub(crate) fn set_fallback(&mut self, handler: Handler) {
+        let roots = [&mut self.GET, &mut self.PUT, &mut self.POST, &mut self.PATCH, &mut self.DELETE, &mut self.OPTIONS]
+            .into_iter()
+            .chain(self.extensions.iter_mut().map(|(_, node)| node));
+
+        for root in roots {
+            if root.fallback.replace(handler.clone()).is_some() {
+                panic!("Conflicting fallback registering: already set, or set by another `Ohkami` mounted at \"/\"")
+            }
+        }
     }
 
     pub(crate) fn merge_another(&mut self, another: ByAnother) {
@@ -268,16 +307,34 @@ impl TrieRouter {
                 self.$method.merge_node(route.clone().into_iter(), another_routes.$method).expect("Can't merge Ohkamis");
             )*};
         } merge! { GET, PUT, POST, PATCH, DELETE, OPTIONS }
+
+        for (ext, node) in another_routes.extensions {
+            self.extension_mut(ext).merge_node(route.clone().into_iter(), node).expect("Can't merge Ohkamis");
+        }
+    }
+
+    /// Root node of the extension method, created if not registered yet
+    fn extension_mut(&mut self, method: ExtensionMethod) -> &mut Node {
+        match self.extensions.iter().position(|(m, _)| *m == method) {
+            Some(i) => &mut self.extensions[i].1,
+            None => {
+                self.extensions.push((method, Node::root()));
+                &mut self.extensions.last_mut().unwrap().1
+            }
+        }
     }
 
     pub(crate) fn into_radix(self) -> super::RadixRouter {
         super::RadixRouter {
-            GET:     self.GET    .into_radix(),
-            PUT:     self.PUT    .into_radix(),
-            POST:    self.POST   .into_radix(),
-            PATCH:   self.PATCH  .into_radix(),
-            DELETE:  self.DELETE .into_radix(),
-            OPTIONS: self.OPTIONS.into_radix(),
+            GET:     self.GET    .into_radix(None),
+            PUT:     self.PUT    .into_radix(None),
+            POST:    self.POST   .into_radix(None),
+            PATCH:   self.PATCH  .into_radix(None),
+            DELETE:  self.DELETE .into_radix(None),
+            OPTIONS: self.OPTIONS.into_radix(None),
+            extensions: self.extensions.into_iter()
+                .map(|(method, node)| (method, node.into_radix(None)))
+                .collect(),
         }
     }
 }
@@ -315,6 +372,9 @@ impl Node {
                 self.merge_here(another)?;
                 Ok(())
             }
+            Some(RouteSection::Wildcard(_)) => Err(String::from(
+                "Can't merge another Ohkami at a wildcard, which matches all the rest of path"
+            )),
             Some(pattern) => match self.machable_child_mut(pattern.clone().into()) {
                 Some(child) => child.merge_node(route_to_merge_root, another),
                 None => {
@@ -336,19 +396,26 @@ impl Node {
         self.fangs_list.add(id, fangs);
     }
 
+    /// `fallback` : the one of the nearest `Ohkami` having it
     #[allow(unused_mut)]
-    fn into_radix(self) -> super::radix::Node {
-        let Node { pattern, mut fangs_list, mut handler, mut children } = self;
+    fn into_radix(self, fallback: Option<Handler>) -> super::radix::Node {
+        let Node { pattern, mut fangs_list, mut handler, fallback: own_fallback, mut children } = self;
+
+        /* a node having its own fallback must remain to be a dead end of a search under it */
+        #[cfg(not(feature="rt_worker"))]
+        let compressible = own_fallback.is_none();
+        let fallback = own_fallback.or(fallback);
 
         let mut patterns = pattern.into_iter().collect::<Vec<_>>();
 
         /* In Cloudflare Workers, this compression may be nothing more than an overhead... */
         #[cfg(not(feature="rt_worker"))]
-        while children.len() == 1 && handler.is_none() {
+        while compressible && children.len() == 1 && handler.is_none() && children[0].fallback.is_none() {
             let Node {
                 pattern:    child_pattern,
                 fangs_list: child_fangses,
                 handler:    child_handler,
+                fallback:   _/* `None` */,
                 children:   child_children,
             } = children.pop(/* pop the single child */).unwrap(/* `children` is empty here */);
 
@@ -359,7 +426,7 @@ impl Node {
             fangs_list.extend(child_fangses);
             
             let child_pattern = child_pattern.unwrap(/* `child` is not root */);
-            if patterns.last().is_some_and(|last| !last.is_param()) && (!child_pattern.is_param()) {
+            if patterns.last().is_some_and(|last| last.to_static().is_some()) && child_pattern.to_static().is_some() {
                 let last_pattern = patterns.pop(/*=== POPing here ===*/).unwrap();
                 let this_static  = last_pattern.to_static().unwrap();
                 let child_static = child_pattern.to_static().unwrap();
@@ -372,18 +439,25 @@ impl Node {
             }
         }
 
-        children.sort_unstable_by(|a, b| match (a.pattern.as_ref().unwrap(), b.pattern.as_ref().unwrap()) {
-            (Pattern::Static(_), Pattern::Param)     => std::cmp::Ordering::Less,
-            (Pattern::Param, Pattern::Static(_))     => std::cmp::Ordering::Greater,
+        /*
+            static ones first, constrained params in registration order,
+            and a wildcard at last ( `radix::Node::search` expects )
+        */
+        children.sort_by(|a, b| match (a.pattern.as_ref().unwrap(), b.pattern.as_ref().unwrap()) {
             (Pattern::Static(a), Pattern::Static(b)) => <[u8]>::cmp(&a, &b).reverse(),
-            _ => std::cmp::Ordering::Equal
+            (a, b) => a.order().cmp(&b.order())
         });
 
+        let handles = handler.is_some();
+        let handler = handler.unwrap_or(Handler::default_not_found());
+
         super::radix::Node {
             patterns:  Box::leak(patterns.into_iter().map(Pattern::into_radix).collect()),
-            children:  Box::leak(children.into_iter().map(Node::into_radix).collect::<Box<[_]>>()),
-            proc:      fangs_list.clone().into_proc_with(handler.unwrap_or(Handler::default_not_found())),
-            __catch__: fangs_list.into_proc_with(Handler::default_not_found()),
+            children:  Box::leak(children.into_iter().map(|c| c.into_radix(fallback.clone())).collect::<Box<[_]>>()),
+            handles,
+            streams_payload: handler.streams_payload,
+            proc:      fangs_list.clone().into_proc_with(handler),
+            __catch__: fangs_list.into_proc_with(fallback.unwrap_or_else(Handler::default_not_found)),
         }
     }
 }
@@ -397,6 +471,7 @@ impl Node {
         Self {
             pattern:    Some(pattern),
             handler:    None,
+            fallback:   None,
             fangs_list: FangsList::new(),
             children:   vec![],
         }
@@ -405,6 +480,7 @@ impl Node {
         Self {
             pattern:    None,
             handler:    None,
+            fallback:   None,
             fangs_list: FangsList::new(),
             children:   vec![],
         }
@@ -461,6 +537,7 @@ impl Node {
             pattern: None, /* another_root must be a root node and has pattern `None` */
             fangs_list: another_root_fangses,
             handler:    another_root_handler,
+            fallback:   another_root_fallback,
             children:   another_root_children,
         } = another_root else {
             panic!("Unexpectedly called `Node::merge_here` where `another_root` is not root node")
@@ -472,6 +549,12 @@ impl Node {
             self.set_handler(h)?;
         }
 
+        if let Some(f) = another_root_fallback {
+            self.fallback.is_none()
+                .then(|| self.fallback = Some(f))
+                .ok_or_else(|| String::from("Conflicting fallback registering"))?;
+        }
+
         for ac in another_root_children {
             self.append_child(ac)?
         }
@@ -483,10 +566,26 @@ impl Node {
 impl Node {
     fn append_child(&mut self, new_child: Node) -> Result<(), String> {
         match new_child.pattern.as_ref().expect("Invalid child node: Child node must have pattern") {
-            Pattern::Param => {
+            /* differently named params may be siblings; `search_from` backtracks across them */
+            Pattern::Param(..) => {
                 self.children.push(new_child);
                 Ok(())
             }
+            new_pattern @ Pattern::Wildcard(_) => {
+                let conflicting = self.children.iter()
+                    .map(|c| c.pattern.as_ref().unwrap())
+                    .find(|p| matches!(p, Pattern::Wildcard(_)) && *p != new_pattern);
+                if let Some(existing) = conflicting {
+                    let __position__ = match &self.pattern {
+                        None    => format!("For the first part of route"),
+                        Some(p) => format!("After {p:?}"),
+                    };
+                    Err(format!("Conflicting wildcard names: {__position__}, both '{existing:?}' and '{new_pattern:?}' are registered"))
+                } else {
+                    self.children.push(new_child);
+                    Ok(())
+                }
+            }
             Pattern::Static(bytes) => {
                 if self.children.iter().find(|c| c.pattern.as_ref().unwrap().to_static().is_some_and(|p| p == bytes.as_ref())).is_some() {
                     let __position__ = match &self.pattern {
@@ -514,30 +613,31 @@ impl Node {
 }
 
 impl Pattern {
-    fn is_param(&self) -> bool {
+    /// static -> constrained param -> param -> wildcard
+    fn order(&self) -> u8 {
         match self {
-            Self::Param => true,
-            Self::Static(_) => false,
+            Self::Static(_)         => 0,
+            Self::Param(_, Some(_)) => 1,
+            Self::Param(_, None)    => 2,
+            Self::Wildcard(_)       => 3,
         }
     }
 
     fn to_static(&self) -> Option<&[u8]> {
         match self {
-            Self::Param         => None,
-            Self::Static(bytes) => Some(&bytes)
+            Self::Param(..) | Self::Wildcard(_) => None,
+            Self::Static(bytes)                 => Some(&bytes)
         }
     }
 
     fn matches(&self, another: &Self) -> bool {
-        match self {
-            Self::Param => another.is_param(),
-            Self::Static{..} => self.to_static() == another.to_static(),
-        }
+        self == another
     }
 
     fn into_radix(self) -> super::radix::Pattern {
         match self {
-            Self::Param                        => super::radix::Pattern::Param,
+            Self::Param(name, c)               => super::radix::Pattern::Param(name, c),
+            Self::Wildcard(name)               => super::radix::Pattern::Wildcard(name),
             Self::Static(Cow::Borrowed(bytes)) => super::radix::Pattern::Static(bytes),
             Self::Static(Cow::Owned(vec))      => super::radix::Pattern::Static(vec.leak()),
         }
@@ -0,0 +1,84 @@
+#![cfg(feature="__rt_native__")]
+
+use ohkami_lib::signal::watch;
+use crate::{FromRequest, Request};
+
+
+/// # Graceful shutdown of the server
+///
+/// Notified when the server starts graceful shutdown, that is, when
+/// the `shutdown` future of `howl_until` is done ( or Ctrl-C for `howl` and `howl_with` ).
+///
+/// Then the server stops accepting connections, closes idle ones,
+/// and waits running sessions to finish for `ServerConfig::shutdown_timeout`,
+/// after which the remaining connections are force-closed.
+///
+/// Long-lived handlers like WebSocket or SSE ones can take this
+/// to finish themselves in time, for example sending a close frame.
+///
+/// <br>
+///
+/// *example.rs*
+/// ```
+/// use ohkami::Shutdown;
+/// use ohkami::ws::{WebSocketContext, WebSocket, Message, CloseFrame, CloseCode};
+///
+/// async fn ws(ctx: WebSocketContext<'_>, shutdown: Shutdown) -> WebSocket {
+///     ctx.connect(move |mut ws| async move {
+///         loop {
+///             tokio::select! {
+///                 message = ws.recv() => match message {
+///                     Ok(Some(Message::Text(text))) => {
+///                         ws.send(Message::Text(text)).await.ok();
+///                     }
+///                     Ok(Some(Message::Close(_)) | None) | Err(_) => break,
+///                     Ok(Some(_)) => (),
+///                 },
+///                 _ = shutdown.notified() => {
+///                     ws.send(Message::Close(Some(CloseFrame {
+///                         code:   CloseCode::Away,
+///                         reason: None,
+///                     }))).await.ok();
+///                     break
+///                 }
+///             }
+///         }
+///     })
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Shutdown(watch::Receiver<bool>);
+
+impl Shutdown {
+    pub(crate) fn channel() -> (watch::Sender<bool>, Self) {
+        let (tx, rx) = watch::channel(false);
+        (tx, Self(rx))
+    }
+
+    /// for a request not served by `howl`s, like one in `testing`
+    fn never() -> Self {
+        Self::channel().1
+    }
+
+    /// Whether graceful shutdown has been started
+    pub fn is_notified(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    /// Wait for graceful shutdown to be started
+    pub async fn notified(&self) {
+        let mut rx = self.0.clone();
+        if rx.wait_for(|started| *started).await.is_err() {
+            /* no more notification */
+            std::future::pending().await
+        }
+    }
+}
+
+impl<'req> FromRequest<'req> for Shutdown {
+    type Error = std::convert::Infallible;
+
+    fn from_request(req: &'req Request) -> Option<Result<Self, Self::Error>> {
+        Some(Ok(req.shutdown.clone().unwrap_or_else(Shutdown::never)))
+    }
+}
@@ -24,18 +24,18 @@ fn parse_path() {
     use std::pin::Pin;
     use ohkami_lib::{Slice, CowSlice};
 
-    fn metadataize(input: &str) -> Box<[u8; BUF_SIZE]> {
-        let mut buf = [0; BUF_SIZE];
+    fn metadataize(input: &str) -> Box<[u8]> {
+        let mut buf = vec![0; BUF_SIZE].into_boxed_slice();
         buf[..input.len().min(BUF_SIZE)]
             .copy_from_slice(&input.as_bytes()[..input.len().min(BUF_SIZE)]);
-        Box::new(buf)
+        buf
     }
 
     macro_rules! assert_parse {
         ($case:expr, $expected:expr) => {
-            let mut actual = Request::init(crate::util::IP_0000);
+            let mut actual = Request::init(Some(crate::util::IP_0000)).with_stream($case.as_bytes());
             let mut actual = unsafe {Pin::new_unchecked(&mut actual)};
-            actual.as_mut().read(&mut $case.as_bytes()).await.ok();
+            actual.as_mut().read(&crate::ServerConfig::default()).await.ok();
 
             let expected = $expected;
 
@@ -68,8 +68,12 @@ fn parse_path() {
     const _CASE_1_LEN: usize = CASE_1.len();
     assert_parse!(CASE_1, Request {
         __buf__: metadataize(CASE_1),
+        __leftover__: CASE_1.len()..CASE_1.len(),
+        __stream__: None,
+        __body__: std::sync::Mutex::new(super::BodyState::None),
         method:  Method::GET,
         path:    Path::from_literal("/hello.html"),
+        authority: None,
         query:   None,
         headers: RequestHeaders::from_iters([
             (RequestHeader::Host,           "www.tutorialspoint.com"),
@@ -80,7 +84,13 @@ fn parse_path() {
         ], None),
         payload: None,
         store:   Store::init(),
-        ip:      crate::util::IP_0000
+        shutdown: None,
+        proxy: None,
+        listener: None,
+        http10: false,
+        length_conflict: false,
+        __io_error__: None,
+        ip:      Some(crate::util::IP_0000)
     });
 
 
@@ -97,8 +107,12 @@ fn parse_path() {
     const _CASE_2_LEN: usize = CASE_2.len();
     assert_parse!(CASE_2, Request {
         __buf__: metadataize(CASE_2),
+        __leftover__: CASE_2.len()..CASE_2.len(),
+        __stream__: None,
+        __body__: std::sync::Mutex::new(super::BodyState::None),
         method:  Method::POST,
         path:    Path::from_literal("/signup"),
+        authority: None,
         query:   None,
         headers: RequestHeaders::from_iters([
             (RequestHeader::Host,           "www.tutorialspoint.com"),
@@ -111,7 +125,13 @@ fn parse_path() {
             br#"{"name":"kanarus","age":20}"#
         ))),
         store: Store::init(),
-        ip:    crate::util::IP_0000
+        shutdown: None,
+        proxy: None,
+        listener: None,
+        http10: false,
+        length_conflict: false,
+        __io_error__: None,
+        ip:    Some(crate::util::IP_0000)
     });
 
     {
@@ -133,8 +153,12 @@ fn parse_path() {
         const _CASE_3_LEN: usize = CASE_3.len();
         assert_parse!(CASE_3, Request {
             __buf__: metadataize(CASE_3),
+            __leftover__: CASE_3.len()..CASE_3.len(),
+            __stream__: None,
+            __body__: std::sync::Mutex::new(super::BodyState::None),
             method:  Method::POST,
             path:    Path::from_literal("/foo.php"),
+            authority: None,
             query:   Some(QueryParams::from([
                 ("query", "1"),
                 ("q2",    "xxx"),
@@ -157,7 +181,160 @@ fn parse_path() {
             ),
             payload: Some(CowSlice::Own(Vec::from("first_name=John&last_name=Doe&action=Submit").into())),
             store:   Store::init(),
-            ip:      crate::util::IP_0000
+            shutdown: None,
+            proxy: None,
+            listener: None,
+            http10: false,
+            length_conflict: false,
+            __io_error__: None,
+            ip:      Some(crate::util::IP_0000)
         });
     }
 }
+
+#[cfg(any(feature="rt_tokio",feature="rt_async-std"))]
+#[crate::__rt__::test] async fn test_parse_chunked_request() {
+    use crate::{Response, Status};
+    use std::pin::Pin;
+
+    async fn parse(input: &'static str, config: crate::ServerConfig) -> (Result<Option<()>, Response>, Request) {
+        let mut req = Request::init(Some(crate::util::IP_0000)).with_stream(input.as_bytes());
+        let mut result = unsafe {Pin::new_unchecked(&mut req)}.read(&config).await;
+        if let Ok(Some(())) = result {
+            match req.load_payload().await {
+                Ok(()) => assert!(req.end_payload()),
+                Err(res) => result = Err(res),
+            }
+        }
+        (result, req)
+    }
+
+    {
+        let (result, req) = parse("\
+            POST /upload HTTP/1.1\r\n\
+            Host: localhost\r\n\
+            Transfer-Encoding: chunked\r\n\
+            \r\n\
+            7;name=value\r\n\
+            Hello, \r\n\
+            6\r\n\
+            World!\r\n\
+            0\r\n\
+            X-Checksum: 42\r\n\
+            \r\n\
+            GET / HTTP/1.1\r\n\r\n\
+        ", crate::ServerConfig::default()).await;
+        assert_eq!(result.unwrap(), Some(()));
+        assert_eq!(req.payload(), Some(&b"Hello, World!"[..]));
+        assert_eq!(&req.__buf__[req.__leftover__.clone()], b"GET / HTTP/1.1\r\n\r\n");
+        assert!(!req.length_conflict);
+    }
+
+    {
+        let (result, req) = parse("\
+            POST /upload HTTP/1.1\r\n\
+            Content-Length: 3\r\n\
+            Transfer-Encoding: chunked\r\n\
+            \r\n\
+            5\r\n\
+            Hello\r\n\
+            0\r\n\
+            \r\n\
+        ", crate::ServerConfig::default()).await;
+        assert_eq!(result.unwrap(), Some(()));
+        assert_eq!(req.payload(), Some(&b"Hello"[..]));
+        assert!(req.length_conflict);
+    }
+
+    for (input, expected) in [
+        ("POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\nxyz\r\nHello\r\n0\r\n\r\n", Status::BadRequest),
+        ("POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nHello!!\r\n0\r\n\r\n", Status::BadRequest),
+        ("POST / HTTP/1.1\r\nTransfer-Encoding: gzip\r\n\r\nHello", Status::BadRequest),
+        ("POST / HTTP/1.1\r\nTransfer-Encoding: gzip, chunked\r\n\r\n0\r\n\r\n", Status::NotImplemented),
+        ("POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nHello\r\n6\r\nWorld!\r\n0\r\n\r\n", Status::PayloadTooLarge),
+        ("POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nHello\r\nffffffffffffffff\r\nWorld!\r\n0\r\n\r\n", Status::PayloadTooLarge),
+    ] {
+        let (result, _) = parse(input, crate::ServerConfig {
+            max_payload_size: 10,
+            ..Default::default()
+        }).await;
+        assert_eq!(result.unwrap_err().status, expected, "{input:?}");
+    }
+}
+
+#[crate::__rt__::test] async fn test_parse_invalid_content_length() {
+    use crate::Status;
+    use std::pin::Pin;
+
+    for input in [
+        "POST / HTTP/1.1\r\nContent-Length: -1\r\n\r\n",
+        "POST / HTTP/1.1\r\nContent-Length: +5\r\n\r\nHello",
+        "POST / HTTP/1.1\r\nContent-Length: 5, 5\r\n\r\nHello",
+        "POST / HTTP/1.1\r\nContent-Length: 5\r\nContent-Length: 5\r\n\r\nHello",
+        "POST / HTTP/1.1\r\nContent-Length: 99999999999999999999999\r\n\r\n",
+        "POST / HTTP/1.1\r\nContent-Length: \r\n\r\n",
+    ] {
+        let mut req = Request::init(Some(crate::util::IP_0000)).with_stream(input.as_bytes());
+        let res = unsafe {Pin::new_unchecked(&mut req)}.read(&crate::ServerConfig::default()).await.unwrap_err();
+        assert_eq!(res.status, Status::BadRequest, "{input:?}");
+        assert_eq!(res.headers.Connection(), Some("close"), "{input:?}");
+    }
+}
+
+#[cfg(any(feature="rt_tokio",feature="rt_async-std"))]
+#[crate::__rt__::test] async fn test_parse_http10_and_absolute_form() {
+    use crate::{Response, Status};
+    use std::pin::Pin;
+
+    async fn parse(input: &'static str) -> (Result<Option<()>, Response>, Request) {
+        let mut req = Request::init(Some(crate::util::IP_0000)).with_stream(input.as_bytes());
+        let result = unsafe {Pin::new_unchecked(&mut req)}.read(&crate::ServerConfig::default()).await;
+        (result, req)
+    }
+
+    {
+        let (result, req) = parse("GET /hello HTTP/1.0\r\nHost: localhost\r\n\r\n").await;
+        assert_eq!(result.unwrap(), Some(()));
+        assert_eq!(&*req.path, "/hello");
+        assert!(req.http10);
+        assert_eq!(req.headers.Connection(), None);
+    }
+    {
+        let (result, req) = parse("GET /hello HTTP/1.0\r\nConnection: Keep-Alive\r\n\r\n").await;
+        assert_eq!(result.unwrap(), Some(()));
+        assert!(req.http10);
+        assert_eq!(req.headers.Connection(), Some("Keep-Alive"));
+    }
+    {
+        let (result, req) = parse("POST /upload HTTP/1.0\r\nExpect: 100-continue\r\nContent-Length: 5\r\n\r\nHello").await;
+        assert_eq!(result.unwrap(), Some(()));
+        assert_eq!(req.payload(), Some(&b"Hello"[..]));
+    }
+    {
+        let (result, req) = parse("GET http://example.com:8080/users/42?q=ohkami HTTP/1.1\r\n\r\n").await;
+        assert_eq!(result.unwrap(), Some(()));
+        assert!(!req.http10);
+        assert_eq!(req.authority(), Some("example.com:8080"));
+        assert_eq!(&*req.path, "/users/42");
+        assert_eq!(req.query.as_ref().unwrap().iter().collect::<Vec<_>>(), [("q".into(), "ohkami".into())]);
+    }
+    {
+        let (result, req) = parse("OPTIONS HTTPS://example.com HTTP/1.1\r\n\r\n").await;
+        assert_eq!(result.unwrap(), Some(()));
+        assert_eq!(req.authority(), Some("example.com"));
+        assert_eq!(&*req.path, "/");
+    }
+    {
+        let (result, req) = parse("GET /hello HTTP/1.1\r\n\r\n").await;
+        assert_eq!(result.unwrap(), Some(()));
+        assert_eq!(req.authority(), None);
+    }
+
+    for (input, expected) in [
+        ("GET / HTTP/2.0\r\n\r\n", Status::HTTPVersionNotSupported),
+        ("POST / HTTP/1.0\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\n", Status::BadRequest),
+    ] {
+        let (result, _) = parse(input).await;
+        assert_eq!(result.unwrap_err().status, expected, "{input:?}");
+    }
+}
@@ -0,0 +1,417 @@
+#![cfg(feature="__rt_native__")]
+
+use std::{io, pin::Pin, time::Duration};
+use std::task::{Context, Poll};
+use crate::__rt__::{AsyncRead, AsyncReader, AsyncWrite, AsyncWriter};
+use crate::Response;
+
+
+/// Size of a chunk read from the connection at once
+/// while streaming a request body
+const CHUNK_SIZE: usize = 1 << 16;
+
+/// Connection a request is read from, owned by the `Request`
+pub(crate) trait Incoming: AsyncRead + Unpin + 'static {
+    /// Where to write an interim response ( `100 Continue` ) to, if any
+    fn interim(&mut self) -> Option<&mut (dyn AsyncWrite + Unpin)> {
+        None
+    }
+
+    /// The session's connection, if this is
+    fn connection(&mut self) -> Option<&mut crate::session::Connection> {
+        None
+    }
+    #[cfg(feature="ws")]
+    fn into_connection(self: Box<Self>) -> Option<crate::session::Connection> {
+        None
+    }
+}
+impl Incoming for &'static [u8] {}
+impl Incoming for InMemory {}
+
+/// Owned request bytes to read a request from, like in `testing`
+#[cfg(feature="rt_tokio")]
+pub(crate) type InMemory = io::Cursor<Vec<u8>>;
+#[cfg(any(feature="rt_async-std", feature="rt_smol", feature="rt_glommio"))]
+pub(crate) type InMemory = ::futures_util::io::Cursor<Vec<u8>>;
+
+/// `Incoming` owned by a `Request`, which the request's `Body` reads from
+pub(crate) struct Stream(Box<dyn Incoming>);
+
+/// SAFETY: `Stream` is only touched by the task handling the connection,
+/// through `&mut Request` or the request's `Body`, and never through `&Stream`
+unsafe impl Send for Stream {}
+unsafe impl Sync for Stream {}
+
+impl Stream {
+    pub(crate) fn new(incoming: impl Incoming) -> Self {
+        Self(Box::new(incoming))
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut dyn Incoming {
+        &mut *self.0
+    }
+
+    #[cfg(feature="ws")]
+    pub(crate) fn into_connection(self) -> Option<crate::session::Connection> {
+        self.0.into_connection()
+    }
+}
+
+/// State of the request body on the connection
+pub(crate) enum BodyState {
+    /// No body, or already buffered in `payload`
+    None,
+    /// Not read yet, or being read, or fully read with possible leftover
+    Unread(Body),
+    /// Taken by a `BodyStream`; it puts the body back when dropped,
+    /// so remaining here means the body is left in an unknown state
+    Streaming,
+}
+
+/// Request body to be read from the connection
+pub(crate) struct Body {
+    stream:  RawStream,
+    /// bytes already read from `stream` but not yet consumed
+    pub(super) buf: Vec<u8>,
+    pub(super) pos: usize,
+    framing: Framing,
+    /// decoded size so far
+    size:    usize,
+    /// length of the request head in `Request::__buf__`
+    pub(super) head_len: usize,
+    pub(super) max_size:        usize,
+    pub(super) max_header_size: usize,
+    /// timeout of each read from `stream`, not of the whole body
+    pub(super) timeout:         Duration,
+    /// whether `100 Continue` should be sent before reading from `stream`
+    pub(super) expects_continue: bool,
+    /// I/O error on `stream`, reported after the request is handled
+    pub(super) error: Option<io::Error>,
+}
+
+enum Framing {
+    /// `Content-Length`, holding the size not yet consumed
+    Length(usize),
+    /// `Transfer-Encoding: chunked`, holding the size of the current chunk not yet consumed
+    Chunked(usize),
+    /// until the end of `stream`, as an HTTP/2 request without `content-length`
+    #[cfg(feature="http2")]
+    Eof,
+    Done,
+    Failed,
+}
+
+impl Body {
+    /// SAFETY: `stream` must not be dropped, replaced, or used by any other
+    /// process while this `Body` is alive
+    pub(super) unsafe fn sized(
+        stream:   &mut Stream,
+        buffered: &[u8],
+        size:     usize,
+        head_len: usize,
+        config:   &crate::ServerConfig,
+    ) -> Self {
+        Self::new(stream, buffered, Framing::Length(size), head_len, config)
+    }
+
+    /// SAFETY: `stream` must not be dropped, replaced, or used by any other
+    /// process while this `Body` is alive
+    pub(super) unsafe fn chunked(
+        stream:   &mut Stream,
+        buffered: &[u8],
+        head_len: usize,
+        config:   &crate::ServerConfig,
+    ) -> Self {
+        Self::new(stream, buffered, Framing::Chunked(0), head_len, config)
+    }
+
+    /// SAFETY: `stream` must not be dropped, replaced, or used by any other
+    /// process while this `Body` is alive
+    #[cfg(feature="http2")]
+    pub(super) unsafe fn until_eof(
+        stream: &mut Stream,
+        config: &crate::ServerConfig,
+    ) -> Self {
+        Self::new(stream, &[], Framing::Eof, 0, config)
+    }
+
+    unsafe fn new(
+        stream:   &mut Stream,
+        buffered: &[u8],
+        framing:  Framing,
+        head_len: usize,
+        config:   &crate::ServerConfig,
+    ) -> Self {
+        Self {
+            stream: RawStream::new(stream),
+            buf:    buffered.to_vec(),
+            pos:    0,
+            framing,
+            size:   0,
+            head_len,
+            max_size:        config.max_payload_size,
+            max_header_size: config.max_header_size,
+            timeout:         config.body_read_timeout,
+            expects_continue: false,
+            error:            None,
+        }
+    }
+
+    /// Whether the whole body has been read
+    pub(crate) fn is_read(&self) -> bool {
+        matches!(self.framing, Framing::Done)
+    }
+
+    /// Next chunk of the body, or `None` when the body is finished ( or failed )
+    pub(crate) async fn next(&mut self) -> Option<Result<Vec<u8>, Response>> {
+        /* stays `Failed` if this is interrupted or fails */
+        let framing = std::mem::replace(&mut self.framing, Framing::Failed);
+        let next = match framing {
+            Framing::Done | Framing::Failed => {
+                self.framing = framing;
+                return None
+            }
+            Framing::Length(0) => Ok((None, Framing::Done)),
+            Framing::Length(remaining) => self.take(remaining).await
+                .map(|chunk| {
+                    let remaining = remaining - chunk.len();
+                    (Some(chunk), Framing::Length(remaining))
+                }),
+            Framing::Chunked(remaining) => self.next_chunked(remaining).await
+                .map(|(chunk, remaining)| match chunk {
+                    Some(chunk) => (Some(chunk), Framing::Chunked(remaining)),
+                    None        => (None, Framing::Done),
+                }),
+            #[cfg(feature="http2")]
+            Framing::Eof => self.take_until_eof().await
+                .map(|chunk| match chunk {
+                    Some(chunk) => (Some(chunk), Framing::Eof),
+                    None        => (None, Framing::Done),
+                }),
+        };
+        match next {
+            Ok((chunk, framing)) => {
+                self.framing = framing;
+                chunk.map(Ok)
+            }
+            Err(res) => Some(Err(res))
+        }
+    }
+
+    /// Read all the remaining body
+    pub(crate) async fn read_all(&mut self) -> Result<Vec<u8>, Response> {
+        if let Framing::Length(remaining) = self.framing {
+            self.framing = Framing::Failed;
+
+            let mut payload = self.buf.split_off(self.pos);
+            self.buf.clear();
+            self.pos = 0;
+
+            let mut filled = payload.len();
+            payload.resize(remaining, 0);
+            self.send_continue().await?;
+            while filled < remaining {
+                match crate::util::timeout_in(self.timeout, self.stream.read(&mut payload[filled..])).await {
+                    None => return Err(Response::RequestTimeout()),
+                    Some(Ok(0)) => return Err(self.failed(io::ErrorKind::UnexpectedEof.into())),
+                    Some(Err(e)) => return Err(self.failed(e)),
+                    Some(Ok(n)) => filled += n,
+                }
+            }
+
+            self.framing = Framing::Done;
+            return Ok(payload)
+        }
+
+        let mut payload = Vec::new();
+        while let Some(chunk) = self.next().await {
+            payload.append(&mut chunk?);
+        }
+        match self.framing {
+            Framing::Done => Ok(payload),
+            _ => Err(Response::BadRequest()),
+        }
+    }
+
+    /// Bytes read beyond the body, or `None` if the body isn't finished
+    pub(crate) fn into_leftover(mut self) -> Option<Vec<u8>> {
+        matches!(self.framing, Framing::Done)
+            .then(|| self.buf.split_off(self.pos))
+    }
+
+    /// Add `size` to the decoded size, checking `max_size`
+    pub(super) fn grow(&mut self, size: usize) -> Result<(), Response> {
+        /* not `self.size + size`, which overflows for a chunk size like `ffffffffffffffff` */
+        if size > self.max_size - self.size {
+            return Err(Response::PayloadTooLarge())
+        }
+        self.size += size;
+        Ok(())
+    }
+
+    /// Up to `max` bytes, from the buffered ones if any, otherwise from `stream`
+    pub(super) async fn take(&mut self, max: usize) -> Result<Vec<u8>, Response> {
+        if self.pos < self.buf.len() {
+            let end = self.buf.len().min(self.pos + max);
+            let chunk = self.buf[self.pos..end].to_vec();
+            self.pos = end;
+            Ok(chunk)
+
+        } else {
+            self.send_continue().await?;
+
+            let mut chunk = vec![0; max.min(CHUNK_SIZE)];
+            match crate::util::timeout_in(self.timeout, self.stream.read(&mut chunk)).await {
+                None => Err(Response::RequestTimeout()),
+                Some(Ok(0)) => Err(self.failed(io::ErrorKind::UnexpectedEof.into())),
+                Some(Err(e)) => Err(self.failed(e)),
+                Some(Ok(n)) => {
+                    chunk.truncate(n);
+                    Ok(chunk)
+                }
+            }
+        }
+    }
+
+    /// Next bytes from `stream` up to `CHUNK_SIZE`, or `None` at the end of `stream`
+    #[cfg(feature="http2")]
+    async fn take_until_eof(&mut self) -> Result<Option<Vec<u8>>, Response> {
+        let mut chunk = vec![0; CHUNK_SIZE];
+        match crate::util::timeout_in(self.timeout, self.stream.read(&mut chunk)).await {
+            None => Err(Response::RequestTimeout()),
+            Some(Ok(0)) => Ok(None),
+            Some(Err(e)) => Err(self.failed(e)),
+            Some(Ok(n)) => {
+                self.grow(n)?;
+                chunk.truncate(n);
+                Ok(Some(chunk))
+            }
+        }
+    }
+
+    /// Keep the I/O error to be reported and respond `400 Bad Request`,
+    /// as the client has most likely gone away
+    #[cold] #[inline(never)]
+    fn failed(&mut self, error: io::Error) -> Response {
+        crate::DEBUG!("[Body] I/O error: {error}");
+        self.error = Some(error);
+        Response::BadRequest()
+    }
+
+    /// Send `100 Continue` if the client is waiting for it before sending the body
+    async fn send_continue(&mut self) -> Result<(), Response> {
+        if std::mem::take(&mut self.expects_continue) {
+            crate::DEBUG!("[Body::send_continue] sending 100 Continue");
+            
+            if let Err(e) = self.stream.write_all(b"HTTP/1.1 100 Continue\r\n\r\n").await {
+                return Err(self.failed(e))
+            }
+            if let Err(e) = self.stream.flush().await {
+                return Err(self.failed(e))
+            }
+        }
+        Ok(())
+    }
+
+    /// Read more bytes from `stream` into `buf`
+    pub(super) async fn fill(&mut self) -> Result<(), Response> {
+        self.buf.drain(..self.pos);
+        self.pos = 0;
+
+        self.send_continue().await?;
+
+        let filled = self.buf.len();
+        self.buf.resize(filled + super::BUF_SIZE, 0);
+        match crate::util::timeout_in(self.timeout, self.stream.read(&mut self.buf[filled..])).await {
+            None => Err(Response::RequestTimeout()),
+            Some(Ok(0)) => Err(self.failed(io::ErrorKind::UnexpectedEof.into())),
+            Some(Err(e)) => Err(self.failed(e)),
+            Some(Ok(n)) => {
+                self.buf.truncate(filled + n);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Pointer to the `Stream` owned by the request this body belongs to,
+/// valid as long as the request is alive.
+/// 
+/// Writing is for interim responses and just discarded if the connection
+/// doesn't accept them.
+struct RawStream(*mut dyn Incoming);
+
+/// SAFETY: the same as `Stream`
+unsafe impl Send for RawStream {}
+
+impl RawStream {
+    fn new(stream: &mut Stream) -> Self {
+        Self(stream.get_mut())
+    }
+}
+
+#[cfg(feature="rt_tokio")]
+impl AsyncRead for Stream {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut tokio::io::ReadBuf<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut *self.0).poll_read(cx, buf)
+    }
+}
+#[cfg(any(feature="rt_async-std", feature="rt_smol", feature="rt_glommio"))]
+impl AsyncRead for Stream {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut *self.0).poll_read(cx, buf)
+    }
+}
+
+#[cfg(feature="rt_tokio")]
+impl AsyncRead for RawStream {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut tokio::io::ReadBuf<'_>) -> Poll<io::Result<()>> {
+        Pin::new(unsafe {&mut *self.as_mut().0}).poll_read(cx, buf)
+    }
+}
+#[cfg(any(feature="rt_async-std", feature="rt_smol", feature="rt_glommio"))]
+impl AsyncRead for RawStream {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        Pin::new(unsafe {&mut *self.as_mut().0}).poll_read(cx, buf)
+    }
+}
+
+#[cfg(feature="rt_tokio")]
+impl AsyncWrite for RawStream {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match unsafe {&mut *self.as_mut().0}.interim() {
+            Some(w) => Pin::new(w).poll_write(cx, buf),
+            None    => Poll::Ready(Ok(buf.len())),
+        }
+    }
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match unsafe {&mut *self.as_mut().0}.interim() {
+            Some(w) => Pin::new(w).poll_flush(cx),
+            None    => Poll::Ready(Ok(())),
+        }
+    }
+    fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        /* never shuts down the connection from here */
+        Poll::Ready(Ok(()))
+    }
+}
+#[cfg(any(feature="rt_async-std", feature="rt_smol", feature="rt_glommio"))]
+impl AsyncWrite for RawStream {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match unsafe {&mut *self.as_mut().0}.interim() {
+            Some(w) => Pin::new(w).poll_write(cx, buf),
+            None    => Poll::Ready(Ok(buf.len())),
+        }
+    }
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match unsafe {&mut *self.as_mut().0}.interim() {
+            Some(w) => Pin::new(w).poll_flush(cx),
+            None    => Poll::Ready(Ok(())),
+        }
+    }
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        /* never closes the connection from here */
+        Poll::Ready(Ok(()))
+    }
+}
@@ -0,0 +1,98 @@
+#![cfg(feature="__rt_native__")]
+
+use super::body::Body;
+use crate::Response;
+
+
+/// Decoding `Transfer-Encoding: chunked` request body.
+///
+/// Chunk extensions and trailer fields are read and discarded.
+impl Body {
+    /// Next piece of decoded data and the size of the current chunk not yet consumed,
+    /// or `None` when the last chunk and trailers are consumed
+    pub(super) async fn next_chunked(&mut self, mut remaining: usize) -> Result<(Option<Vec<u8>>, usize), Response> {
+        if remaining == 0 {
+            let size_line = self.line(self.max_header_size).await?.ok_or_else(Response::BadRequest)?;
+            let size = parse_chunk_size(&self.buf[size_line]).ok_or_else(Response::BadRequest)?;
+            if size == 0 {
+                self.trailers().await?;
+                return Ok((None, 0))
+            }
+            self.grow(size)?;
+            remaining = size;
+        }
+
+        let data = self.take(remaining).await?;
+        remaining -= data.len();
+        if remaining == 0 {
+            self.crlf().await?;
+        }
+
+        Ok((Some(data), remaining))
+    }
+
+    async fn trailers(&mut self) -> Result<(), Response> {
+        let mut trailers_size = 0;
+        loop {
+            let trailer = self.line(self.max_header_size.saturating_sub(trailers_size)).await?
+                .ok_or_else(Response::RequestHeaderFieldsTooLarge)?;
+            if trailer.is_empty() {break Ok(())}
+            trailers_size += trailer.len() + "\r\n".len();
+        }
+    }
+
+    /// Next line without CRLF as a range of `self.buf`,
+    /// or `None` if it's longer than `limit`
+    async fn line(&mut self, limit: usize) -> Result<Option<std::ops::Range<usize>>, Response> {
+        let mut searched = 0;
+        loop {
+            if let Some(i) = self.buf[self.pos + searched..].windows(2).position(|w| w == b"\r\n") {
+                let line = self.pos..(self.pos + searched + i);
+                self.pos = line.end + "\r\n".len();
+                return Ok((line.len() <= limit).then_some(line))
+            }
+            searched = (self.buf.len() - self.pos).saturating_sub(1);
+
+            if searched > limit {
+                return Ok(None)
+            }
+            self.fill().await?;
+        }
+    }
+
+    async fn crlf(&mut self) -> Result<(), Response> {
+        while self.buf.len() - self.pos < "\r\n".len() {
+            self.fill().await?;
+        }
+        if &self.buf[self.pos..self.pos + 2] != b"\r\n" {
+            return Err(Response::BadRequest())
+        }
+        self.pos += 2;
+        Ok(())
+    }
+}
+
+/// `chunk-size [ chunk-ext ]`
+fn parse_chunk_size(line: &[u8]) -> Option<usize> {
+    let size = match line.iter().position(|b| *b == b';') {
+        Some(i) => &line[..i],
+        None    => line,
+    }.trim_ascii_end();
+
+    if size.is_empty() || size.len() > 2 * size_of::<usize>() {
+        return None
+    }
+    size.iter().try_fold(0, |n, b| Some(16 * n + (*b as char).to_digit(16)? as usize))
+}
+
+#[cfg(test)]
+#[test] fn test_parse_chunk_size() {
+    assert_eq!(parse_chunk_size(b"0"), Some(0));
+    assert_eq!(parse_chunk_size(b"1a"), Some(26));
+    assert_eq!(parse_chunk_size(b"FF ; name=value"), Some(255));
+    assert_eq!(parse_chunk_size(b"10;ext"), Some(16));
+    assert_eq!(parse_chunk_size(b""), None);
+    assert_eq!(parse_chunk_size(b"xyz"), None);
+    assert_eq!(parse_chunk_size(b"+1"), None);
+    assert_eq!(parse_chunk_size(b"fffffffffffffffff"), None);
+}
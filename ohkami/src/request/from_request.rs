@@ -39,6 +39,10 @@ pub trait FromRequest<'req>: Sized {
     
     fn from_request(req: &'req Request) -> Option<Result<Self, Self::Error>>;
 
+    #[doc(hidden)]
+    /// Whether this takes the request body as a stream.
+    /// If so, the body isn't buffered into `payload` before the handler.
+    const STREAMS_PAYLOAD: bool = false;
 }
 const _: () = {
     impl<'req> FromRequest<'req> for &'req Request {
@@ -49,6 +53,7 @@ const _: () = {
     }
     impl<'req, FR: FromRequest<'req>> FromRequest<'req> for Option<FR> {
         type Error = FR::Error;
+        const STREAMS_PAYLOAD: bool = FR::STREAMS_PAYLOAD;
         #[inline]
         fn from_request(req: &'req Request) -> Option<Result<Self, Self::Error>> {
             match FR::from_request(req) {
@@ -1,4 +1,4 @@
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Method {
     GET,
     PUT,
@@ -7,6 +7,59 @@ pub enum Method {
     DELETE,
     HEAD,
     OPTIONS,
+    /// Any other method like WebDAV's `PROPFIND`, `MKCOL`, `LOCK`, or `QUERY`
+    ///
+    /// Routed by `"/path".method("PROPFIND", handler)`
+    Extension(ExtensionMethod),
+}
+
+/// Method other than the ones Ohkami handles by itself,
+/// held inline as a token of at most `ExtensionMethod::MAX_LEN` bytes
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExtensionMethod {
+    len:   u8,
+    token: [u8; ExtensionMethod::MAX_LEN],
+}
+
+impl ExtensionMethod {
+    pub const MAX_LEN: usize = 23;
+
+    /// `None` if `method` is not a valid token or longer than `MAX_LEN`.
+    ///
+    /// Method is case-sensitive, so `propfind` is not `PROPFIND`.
+    pub const fn new(method: &str) -> Option<Self> {
+        let bytes = method.as_bytes();
+        if bytes.len() > Self::MAX_LEN || !is_token(bytes) {
+            return None
+        }
+
+        let mut token = [0; Self::MAX_LEN];
+        let mut i = 0; while i < bytes.len() {
+            token[i] = bytes[i];
+            i += 1
+        }
+        Some(Self { len: bytes.len() as u8, token })
+    }
+
+    #[inline] pub const fn as_str(&self) -> &str {
+        let (token, _) = self.token.split_at(self.len as usize);
+        // SAFETY: `token` consists of ASCII tchars ( checked in `new` )
+        unsafe {std::str::from_utf8_unchecked(token)}
+    }
+}
+
+/// `token` of RFC 9110 : 1*tchar
+pub(crate) const fn is_token(bytes: &[u8]) -> bool {
+    if bytes.is_empty() {return false}
+
+    let mut i = 0; while i < bytes.len() {
+        if !matches!(bytes[i],
+            b'!' | b'#' | b'$' | b'%' | b'&' | b'\'' | b'*' | b'+' | b'-' | b'.' |
+            b'^' | b'_' | b'`' | b'|' | b'~' | b'0'..=b'9' | b'a'..=b'z' | b'A'..=b'Z'
+        ) {return false}
+        i += 1
+    }
+    true
 }
 
 impl Method {
@@ -19,24 +72,17 @@ impl Method {
             b"DELETE"  => Some(Self::DELETE),
             b"HEAD"    => Some(Self::HEAD),
             b"OPTIONS" => Some(Self::OPTIONS),
-            _ => None
-        }
-    }
-    #[cfg(feature="rt_worker")]
-    #[inline(always)] pub(crate) const fn from_worker(w: ::worker::Method) -> Option<Self> {
-        match w {
-            ::worker::Method::Get     => Some(Self::GET),
-            ::worker::Method::Put     => Some(Self::PUT),
-            ::worker::Method::Post    => Some(Self::POST),
-            ::worker::Method::Patch   => Some(Self::PATCH),
-            ::worker::Method::Delete  => Some(Self::DELETE),
-            ::worker::Method::Head    => Some(Self::HEAD),
-            ::worker::Method::Options => Some(Self::OPTIONS),
-            _ => None
+            _ => match std::str::from_utf8(bytes) {
+                Err(_)     => None,
+                Ok(method) => match ExtensionMethod::new(method) {
+                    Some(ext) => Some(Self::Extension(ext)),
+                    None      => None,
+                }
+            }
         }
     }
 
-    #[inline] pub const fn as_str(&self) -> &'static str {
+    #[inline] pub const fn as_str(&self) -> &str {
         match self {
             Self::GET     => "GET",
             Self::PUT     => "PUT",
@@ -45,6 +91,7 @@ impl Method {
             Self::DELETE  => "DELETE",
             Self::HEAD    => "HEAD",
             Self::OPTIONS => "OPTIONS",
+            Self::Extension(ext) => ext.as_str(),
         }
     }
 }
@@ -75,29 +122,33 @@ impl Method {
 const _: () = {
     impl std::fmt::Display for Method {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            f.write_str(match self {
-                Self::GET     => "GET",
-                Self::PUT     => "PUT",
-                Self::POST    => "POST",
-                Self::PATCH   => "PATCH",
-                Self::DELETE  => "DELETE",
-                Self::HEAD    => "HEAD",
-                Self::OPTIONS => "OPTIONS",
-            })
+            f.write_str(self.as_str())
         }
     }
-    
+
     impl std::fmt::Debug for Method {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            f.write_str(match self {
-                Self::GET     => "GET",
-                Self::PUT     => "PUT",
-                Self::POST    => "POST",
-                Self::PATCH   => "PATCH",
-                Self::DELETE  => "DELETE",
-                Self::HEAD    => "HEAD",
-                Self::OPTIONS => "OPTIONS",
-            })
+            f.write_str(self.as_str())
+        }
+    }
+
+    impl std::fmt::Debug for ExtensionMethod {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(self.as_str())
         }
     }
 };
+
+#[cfg(test)]
+#[test] fn test_method_from_bytes() {
+    assert_eq!(Method::from_bytes(b"GET"), Some(Method::GET));
+    assert_eq!(Method::from_bytes(b"PROPFIND"), Some(Method::Extension(ExtensionMethod::new("PROPFIND").unwrap())));
+    assert_eq!(Method::from_bytes(b"PROPFIND").unwrap().as_str(), "PROPFIND");
+    assert_eq!(Method::from_bytes(b"VERSION-CONTROL").unwrap().as_str(), "VERSION-CONTROL");
+    assert_ne!(Method::from_bytes(b"get"), Some(Method::GET));
+
+    assert_eq!(Method::from_bytes(b""), None);
+    assert_eq!(Method::from_bytes(b"PROP FIND"), None);
+    assert_eq!(Method::from_bytes(b"GET\r\n"), None);
+    assert_eq!(Method::from_bytes(b"A_VERY_LONG_EXTENSION_METHOD"), None);
+}
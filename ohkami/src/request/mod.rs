@@ -1,5 +1,5 @@
 mod method;
-pub use method::Method;
+pub use method::{Method, ExtensionMethod};
 
 mod path;
 pub(crate) use path::Path;
@@ -18,6 +18,12 @@ pub(crate) use store::Store;
 mod from_request; 
 pub use from_request::*;
 
+mod body;
+#[cfg(feature="__rt_native__")]
+pub(crate) use body::{BodyState, Body, Incoming, InMemory, Stream};
+
+mod chunked;
+
 #[cfg(test)] mod _test_parse;
 #[cfg(test)] mod _test_extract;
 #[cfg(test)] mod _test_headers;
@@ -38,8 +44,50 @@ use {
 #[cfg(feature="__rt_native__")]
 pub(crate) const BUF_SIZE: usize = 1 << 10;
 #[cfg(feature="__rt_native__")]
+pub(crate) const HEADER_SIZE_LIMIT: usize = 1 << 13;
+#[cfg(feature="__rt_native__")]
 pub(crate) const PAYLOAD_LIMIT: usize = 1 << 32;
 
+/// `501 Not Implemented` for a method Ohkami can't hold, `400 Bad Request` for an invalid one
+#[cfg(feature="__rt__")]
+fn parse_method(bytes: &[u8]) -> Result<Method, crate::Response> {
+    Method::from_bytes(bytes).ok_or_else(|| if method::is_token(bytes) {
+        crate::Response::NotImplemented().with_headers(|h| h.Connection("close"))
+    } else {
+        crate::Response::BadRequest().with_headers(|h| h.Connection("close"))
+    })
+}
+
+/// `Content-Length` is `1*DIGIT`, so `None` for a sign, a list or an overflowing one
+#[cfg(feature="__rt__")]
+fn parse_content_length(bytes: &[u8]) -> Option<usize> {
+    if bytes.is_empty() || !bytes.iter().all(u8::is_ascii_digit) {
+        return None
+    }
+    std::str::from_utf8(bytes).ok()?.parse::<usize>().ok()
+}
+
+/// `http://authority/path` → `Some((authority, /path))`
+#[cfg(feature="__rt_native__")]
+fn split_absolute_form(target: &[u8]) -> Option<(&[u8], &[u8])> {
+    let scheme_len = if target.get(..7)?.eq_ignore_ascii_case(b"http://") {7}
+        else if target.get(..8)?.eq_ignore_ascii_case(b"https://") {8}
+        else {return None};
+
+    let rest = &target[scheme_len..];
+    let (authority, path) = match rest.iter().position(|b| *b == b'/') {
+        Some(i) => rest.split_at(i),
+        None    => (rest, &b"/"[..]),
+    };
+    (!authority.is_empty()).then_some((authority, path))
+}
+
+/// Whether a comma-separated header value contains `token` ( case-insensitive )
+#[cfg(feature="__rt_native__")]
+pub(crate) fn has_token(value: &str, token: &str) -> bool {
+    value.split(',').any(|t| t.trim().eq_ignore_ascii_case(token))
+}
+
 /// # HTTP Request
 /// 
 /// Composed of
@@ -99,7 +147,11 @@ pub(crate) const PAYLOAD_LIMIT: usize = 1 << 32;
 /// ```
 pub struct Request {
     #[cfg(feature="__rt_native__")]
-    pub(super/* for test */) __buf__: Box<[u8; BUF_SIZE]>,
+    pub(super/* for test */) __buf__: Box<[u8]>,
+    /// Range of `__buf__` already read from the stream but not belonging to
+    /// this request, that is, the head of the next pipelined request
+    #[cfg(feature="__rt_native__")]
+    pub(super/* for test */) __leftover__: std::ops::Range<usize>,
 
     #[cfg(feature="rt_worker")]
     pub(super/* for test */) __url__: std::mem::MaybeUninit<::worker::Url>,
@@ -112,9 +164,10 @@ pub struct Request {
     /// 
     /// ---
     /// 
-    /// **Note** : In current version, custom HTTP methods are *not supported*,
-    /// in other words, now Ohkami just knows `GET`, `PUT`, `POST`, `PATCH`,
-    /// `DELETE`, `HEAD`, `OPTIONS`.
+    /// **Note** : Methods other than `GET`, `PUT`, `POST`, `PATCH`, `DELETE`,
+    /// `HEAD`, `OPTIONS` are held as `Method::Extension` ( up to
+    /// `ExtensionMethod::MAX_LEN` bytes, otherwise `501 Not Implemented` ),
+    /// and routed to handlers registered by `.method(...)`.
     pub method: Method,
 
     /// Request path of this request
@@ -124,11 +177,15 @@ pub struct Request {
     /// 
     /// ---
     /// 
-    /// **Note** : In current version, path with schema and origin in request line
-    /// is *not supported*, in other words, now Ohkami just handles requests like
-    /// `GET /path HTTP/1.1`, not `GET http://whatthe.fxxx/path HTTP/1.1`
+    /// **Note** : For an absolute-form request target like
+    /// `GET http://example.com/path HTTP/1.1`, this is just `/path`
+    /// and the authority `example.com` is available by `.authority()`.
     pub path: Path,
 
+    #[cfg(feature="__rt_native__")]
+    /// Authority of an absolute-form request target
+    authority: Option<Slice>,
+
     /// Query params of this request
     /// 
     /// In handler, using a struct of expected schema
@@ -160,15 +217,53 @@ pub struct Request {
 
     pub payload: Option<CowSlice>,
 
+    #[cfg(feature="__rt_native__")]
+    /// Connection this request is read from, which `__body__` reads the rest from
+    __stream__: Option<Stream>,
+
+    #[cfg(feature="__rt_native__")]
+    /// Request body not yet read into `payload`
+    pub(crate) __body__: std::sync::Mutex<BodyState>,
+
     store: Store,
 
     #[cfg(feature="__rt_native__")]
-    /// Remote ( directly connected ) peer's IP address
+    /// Graceful shutdown of the server serving this request
+    pub(crate) shutdown: Option<crate::Shutdown>,
+
+    #[cfg(feature="__rt_native__")]
+    /// PROXY protocol header of the connection, with `ServerConfig::proxy_protocol`
+    pub(crate) proxy: Option<std::sync::Arc<crate::ProxyHeader>>,
+
+    #[cfg(feature="__rt_native__")]
+    /// Listener that accepted the connection, `None` in `testing`
+    pub(crate) listener: Option<std::sync::Arc<crate::ListenerInfo>>,
+
+    #[cfg(feature="__rt_native__")]
+    /// Whether this is an HTTP/1.0 request, closing the connection
+    /// unless `Connection: keep-alive` and not taking a chunked response
+    pub(crate) http10: bool,
+
+    #[cfg(feature="__rt_native__")]
+    /// Whether both `Transfer-Encoding` and `Content-Length` are sent,
+    /// closing the connection after the response ( RFC 9112 §6.1 )
+    pub(crate) length_conflict: bool,
+
+    #[cfg(feature="__rt_native__")]
+    /// I/O error on the connection while reading this request,
+    /// taken by the session to report
+    pub(crate) __io_error__: Option<std::io::Error>,
+
+    #[cfg(feature="__rt_native__")]
+    /// Remote ( directly connected ) peer's IP address,
+    /// or `None` for a connection via Unix domain socket
     /// 
     /// ---
     /// 
     /// **NOTE** : If a proxy is in front of Ohkami, this will be the proxy's address
-    pub ip: std::net::IpAddr,
+    /// unless `ServerConfig::proxy_protocol` is enabled.
+    /// `fang::ClientIp` takes the address told by the trusted proxies.
+    pub ip: Option<std::net::IpAddr>,
 }
 
 impl Request {
@@ -176,11 +271,13 @@ impl Request {
     #[inline]
     pub(crate) fn init(
         #[cfg(feature="__rt_native__")]
-        ip: std::net::IpAddr
+        ip: Option<std::net::IpAddr>
     ) -> Self {
         Self {
             #[cfg(feature="__rt_native__")]
-            __buf__: Box::new([0; BUF_SIZE]),
+            __buf__: Box::new([]),
+            #[cfg(feature="__rt_native__")]
+            __leftover__: 0..0,
 
             #[cfg(feature="rt_worker")]
             __url__: std::mem::MaybeUninit::uninit(),
@@ -191,27 +288,91 @@ impl Request {
 
             method:  Method::GET,
             path:    Path::uninit(),
+            #[cfg(feature="__rt_native__")]
+            authority: None,
             query:   None,
             headers: RequestHeaders::init(),
             payload: None,
+            #[cfg(feature="__rt_native__")]
+            __stream__: None,
+            #[cfg(feature="__rt_native__")]
+            __body__: std::sync::Mutex::new(BodyState::None),
             store:   Store::init(),
             
+            #[cfg(feature="__rt_native__")]
+            shutdown: None,
+            #[cfg(feature="__rt_native__")]
+            proxy: None,
+            #[cfg(feature="__rt_native__")]
+            listener: None,
+            #[cfg(feature="__rt_native__")]
+            http10: false,
+            #[cfg(feature="__rt_native__")]
+            length_conflict: false,
+            #[cfg(feature="__rt_native__")]
+            __io_error__: None,
             #[cfg(feature="__rt_native__")]
             ip,
         }
     }
+    /// Set the connection to read requests from
+    #[cfg(feature="__rt_native__")]
+    #[inline]
+    pub(crate) fn with_stream(mut self, stream: impl Incoming) -> Self {
+        self.__stream__ = Some(Stream::new(stream));
+        self
+    }
+
+    /// Set the bytes already read from the stream, as the head of the first request
+    #[cfg(feature="http2")]
+    #[inline]
+    pub(crate) fn with_preread(mut self, bytes: &[u8]) -> Self {
+        if !bytes.is_empty() {
+            let mut buf = vec![0; BUF_SIZE.max(bytes.len())].into_boxed_slice();
+            buf[..bytes.len()].copy_from_slice(bytes);
+            self.__buf__ = buf;
+            self.__leftover__ = 0..bytes.len();
+        }
+        self
+    }
+
+    /// The session's connection this request is read from, to write the response to.
+    /// The body, if left, can't be read after this.
+    #[cfg(feature="__rt_native__")]
+    pub(crate) fn connection(&mut self) -> Option<&mut crate::session::Connection> {
+        *self.__body__.get_mut().unwrap_or_else(std::sync::PoisonError::into_inner) = BodyState::None;
+        self.__stream__.as_mut()?.get_mut().connection()
+    }
+
+    /// Take back the session's connection this request is read from
+    #[cfg(all(feature="__rt_native__", feature="ws"))]
+    pub(crate) fn take_connection(&mut self) -> Option<crate::session::Connection> {
+        *self.__body__.get_mut().unwrap_or_else(std::sync::PoisonError::into_inner) = BodyState::None;
+        self.__stream__.take()?.into_connection()
+    }
+
     #[cfg(feature="__rt_native__")]
     #[inline]
     pub(crate) fn clear(&mut self) {
-        if self.__buf__[0] != 0 {
-            for b in &mut *self.__buf__ {
+        if self.__buf__.first().is_some_and(|b| *b != 0) {
+            /* keep the leftover bytes at the head of `__buf__` for the next request */
+            let leftover = std::mem::take(&mut self.__leftover__);
+            self.__buf__.copy_within(leftover.clone(), 0);
+            for b in &mut self.__buf__[leftover.len()..] {
                 match b {0 => break, _ => *b = 0}
             }
-            self.path  = Path::uninit();
+            self.__leftover__ = 0..leftover.len();
+
+            self.path.clear();
+            self.authority = None;
             self.query = None;
             self.headers.clear();
             self.payload = None;
+            *self.__body__.get_mut().unwrap_or_else(std::sync::PoisonError::into_inner) = BodyState::None;
             self.store.clear();
+            self.http10 = false;
+            self.length_conflict = false;
+            self.__io_error__ = None;
         } /* else: just after `init`ed or `clear`ed */
     }
 
@@ -219,20 +380,78 @@ impl Request {
     #[inline]
     pub(crate) async fn read(
         mut self: Pin<&mut Self>,
-        stream:   &mut (impl AsyncReader + Unpin),
+        config:   &crate::ServerConfig,
+    ) -> Result<Option<()>, crate::Response> {
+        /* moving the `Box` out and back keeps the `Stream` where a `Body` points to */
+        let Some(mut stream) = self.__stream__.take() else {return Ok(None)};
+        let result = self.as_mut().read_from(&mut stream, config).await;
+        self.__stream__ = Some(stream);
+        result
+    }
+
+    #[cfg(feature="__rt_native__")]
+    #[inline]
+    async fn read_from(
+        mut self: Pin<&mut Self>,
+        stream:   &mut Stream,
+        config:   &crate::ServerConfig,
     ) -> Result<Option<()>, crate::Response> {
         use crate::Response;
 
-        match stream.read(&mut *self.__buf__).await {
-            Ok (0) => return Ok(None),
-            Err(e) => return match e.kind() {
-                std::io::ErrorKind::ConnectionReset => Ok(None),
-                _ => Err((|err| {
-                    crate::warning!("Failed to read stream: {err}");
-                    Response::InternalServerError()
-                })(e))
-            },
-            _ => ()
+        if self.__buf__.is_empty() {
+            /* grows up to `config.max_header_size` when a request head doesn't fit in */
+            self.__buf__ = vec![0; BUF_SIZE.min(config.max_header_size)].into_boxed_slice();
+        }
+
+        /* the client has gone away or the connection is broken, so just close it */
+        #[cold] #[inline(never)]
+        fn read_error(mut req: Pin<&mut Request>, e: std::io::Error) -> Result<Option<()>, Response> {
+            req.__io_error__ = Some(e);
+            Ok(None)
+        }
+
+        #[inline]
+        fn has_head_end(bytes: &[u8]) -> bool {
+            bytes.windows(4).any(|w| w == b"\r\n\r\n")
+        }
+
+        /* bytes of this request already read with the previous one ( pipelined ) */
+        let mut n = std::mem::take(&mut self.__leftover__).len();
+
+        if n == 0 {
+            /* idle until the first bytes of the next request */
+            n = match crate::util::timeout_in(config.keepalive_timeout, stream.read(&mut self.__buf__)).await {
+                None => return Ok(None),
+                Some(Ok (0)) => return Ok(None),
+                Some(Err(e)) => return read_error(self, e),
+                Some(Ok(n)) => n
+            };
+        }
+
+        if !has_head_end(&self.__buf__[..n]) {
+            let buf = &mut self.__buf__;
+            match crate::util::timeout_in(config.header_read_timeout, async {
+                loop {
+                    if n == buf.len() {
+                        if buf.len() >= config.max_header_size {break Ok(false)}
+
+                        let mut grown = vec![0; (2 * buf.len()).min(config.max_header_size)].into_boxed_slice();
+                        grown[..n].copy_from_slice(&buf[..n]);
+                        *buf = grown;
+                    }
+
+                    let m = match stream.read(&mut buf[n..]).await? {
+                        0 => break Err(std::io::ErrorKind::UnexpectedEof.into()),
+                        m => {n += m; m}
+                    };
+                    if has_head_end(&buf[(n - m).saturating_sub(3)..n]) {break Ok(true)}
+                }
+            }).await {
+                None => return Err(Response::RequestTimeout().with_headers(|h| h.Connection("close"))),
+                Some(Err(e)) => return read_error(self, e),
+                Some(Ok(false)) => return Err(Response::RequestHeaderFieldsTooLarge().with_headers(|h| h.Connection("close"))),
+                Some(Ok(true)) => ()
+            }
         }
 
         let mut r = Reader::new(unsafe {
@@ -240,24 +459,31 @@ impl Request {
             // to resolve immutable/mutable borrowing
             // 
             // SAFETY: `self.__buf__` itself is immutable
-            Slice::from_bytes(&*self.__buf__).as_bytes()
+            Slice::from_bytes(&self.__buf__[..n]).as_bytes()
         });
 
-        match Method::from_bytes(r.read_while(|b| b != &b' ')) {
-            None => return Ok(None),
-            Some(method) => self.method = method
-        }
+        self.method = parse_method(r.read_while(|b| b != &b' '))?;
 
         r.next_if(|b| *b==b' ').ok_or_else(Response::BadRequest)?;
         
-        self.path.init_with_request_bytes(r.read_while(|b| !matches!(b, b' ' | b'?')))?;
+        let target = r.read_while(|b| !matches!(b, b' ' | b'?'));
+        match split_absolute_form(target) {
+            None => self.path.init_with_request_bytes(target)?,
+            Some((authority, path)) => {
+                self.authority = Some(Slice::from_bytes(authority));
+                self.path.init_with_request_bytes(path)?;
+            }
+        }
 
-        if r.consume_oneof([" ", "?"]).unwrap() == 1 {
+        if r.consume_oneof([" ", "?"]).ok_or_else(Response::BadRequest)? == 1 {
             self.query = Some(QueryParams::new(r.read_while(|b| b != &b' ')));
             r.advance_by(1);
         }
 
-        r.consume("HTTP/1.1\r\n").ok_or_else(Response::HTTPVersionNotSupported)?;
+        let http10 = match r.consume_oneof(["HTTP/1.1\r\n", "HTTP/1.0\r\n"]) {
+            Some(version) => version == 1,
+            None => return Err(Response::HTTPVersionNotSupported()),
+        };
 
         while r.consume("\r\n").is_none() {
             let key_bytes = r.read_while(|b| b != &b':');
@@ -272,56 +498,228 @@ impl Request {
             }
         }
 
+        self.http10 = http10;
+        /* HTTP/1.0 doesn't know chunked encoding, so the framing is unreliable */
+        if http10 && self.headers.TransferEncoding().is_some() {
+            return Err(Response::BadRequest().with_headers(|h| h.Connection("close")))
+        }
+
+        /* `100-continue` is the only expectation defined, and to be ignored in HTTP/1.0 */
+        let expects_continue = match self.headers.Expect() {
+            None => false,
+            Some(expect) if expect.eq_ignore_ascii_case("100-continue") => !http10,
+            Some(_) => return Err(Response::ExpectationFailed().with_headers(|h| h.Connection("close"))),
+        };
+
+        /* `Transfer-Encoding` overrides `Content-Length`, but the sender may have framed it otherwise */
+        self.length_conflict = self.headers.TransferEncoding().is_some() && self.headers.ContentLength().is_some();
+
+        if let Some(transfer_encoding) = self.headers.get(RequestHeader::TransferEncoding) {
+            let mut codings = transfer_encoding.split(',').map(str::trim);
+            if !codings.next_back().is_some_and(|last| last.eq_ignore_ascii_case("chunked")) {
+                return Err(Response::BadRequest().with_headers(|h| h.Connection("close")))
+            }
+            if codings.next().is_some() {
+                return Err(Response::NotImplemented().with_headers(|h| h.Connection("close")))
+            }
+
+            /* read later by `load_payload` or `BodyStream` */
+            let mut body = unsafe {
+                // SAFETY: `stream` is owned by `self` and not used by others until `connection` or `clear`
+                body::Body::chunked(stream, r.remaining(), n - r.remaining().len(), config)
+            };
+            body.expects_continue = expects_continue;
+            *self.__body__.get_mut().unwrap_or_else(std::sync::PoisonError::into_inner) = BodyState::Unread(body);
+
+            return Ok(Some(()))
+        }
+
         let content_length = match self.headers.get_raw(RequestHeader::ContentLength) {
-            Some(v) => unsafe {v.as_bytes()}.into_iter().fold(0, |len, b| 10*len + (*b - b'0') as usize),
+            Some(v) => parse_content_length(unsafe {v.as_bytes()})
+                .ok_or_else(|| Response::BadRequest().with_headers(|h| h.Connection("close")))?,
             None    => 0,
         };
+        let remaining_len = r.remaining().len();
         match content_length {
             0 => (),
-            PAYLOAD_LIMIT.. => return Err((|| Response::PayloadTooLarge())()),
-            _ => self.payload = Some(Request::read_payload(
-                stream,
-                r.remaining(),
-                content_length,
-            ).await)
+            n if n > config.max_payload_size => return Err(Response::PayloadTooLarge().with_headers(|h| h.Connection("close"))),
+            n if n <= remaining_len => self.payload = Some(CowSlice::Ref(unsafe {
+                Slice::new_unchecked(r.remaining().as_ptr(), n)
+            })),
+            /* read later by `load_payload` or `BodyStream` */
+            _ => {
+                let mut body = unsafe {
+                    // SAFETY: `stream` is owned by `self` and not used by others until `connection` or `clear`
+                    body::Body::sized(stream, r.remaining(), content_length, n - remaining_len, config)
+                };
+                body.expects_continue = expects_continue;
+                *self.__body__.get_mut().unwrap_or_else(std::sync::PoisonError::into_inner) = BodyState::Unread(body);
+            }
         }
 
+        self.__leftover__ = (n - remaining_len.saturating_sub(content_length))..n;
+
         Ok(Some(()))
     }
 
+    /// Build from the head of an HTTP/2 request,
+    /// whose body is read later from the stream unless `end_of_stream`
+    #[cfg(feature="http2")]
+    pub(crate) fn read_h2(
+        mut self: Pin<&mut Self>,
+        head:          ::http::request::Parts,
+        end_of_stream: bool,
+        config:        &crate::ServerConfig,
+    ) -> Result<(), crate::Response> {
+        use crate::Response;
+        use ::http::header::{HOST, COOKIE, CONTENT_LENGTH};
+
+        self.method = parse_method(head.method.as_str().as_bytes())?;
+
+        /* copy the head into `__buf__` for `path`, `query` and `headers` to refer to */
+        let mut buf = Vec::with_capacity(BUF_SIZE);
+        let mut push = |bytes: &[u8]| {
+            buf.extend_from_slice(bytes);
+            (buf.len() - bytes.len())..buf.len()
+        };
+        let target = push(head.uri.path_and_query().map_or("/", |pq| pq.as_str()).as_bytes());
+        /* `:authority` stands for `Host` */
+        let host = head.uri.authority()
+            .filter(|_| !head.headers.contains_key(HOST))
+            .map(|authority| push(authority.as_str().as_bytes()));
+        /* split `cookie`s are to be joined by `; ` ( RFC 9113, 8.2.3 ) */
+        let cookie = head.headers.contains_key(COOKIE).then(|| push(&head.headers.get_all(COOKIE).iter()
+            .map(|cookie| cookie.as_bytes())
+            .collect::<Vec<_>>().join(&b"; "[..])
+        ));
+        let fields = head.headers.iter()
+            .filter(|(name, _)| *name != COOKIE)
+            .map(|(name, value)| (push(name.as_str().as_bytes()), push(value.as_bytes())))
+            .collect::<Vec<_>>();
+
+        self.__buf__ = buf.into_boxed_slice();
+        let buf = unsafe {
+            // SAFETY: `self.__buf__` itself is immutable
+            Slice::from_bytes(&self.__buf__).as_bytes()
+        };
+
+        let target = &buf[target];
+        match target.iter().position(|b| *b == b'?') {
+            None => self.path.init_with_request_bytes(target)?,
+            Some(q) => {
+                self.path.init_with_request_bytes(&target[..q])?;
+                self.query = Some(QueryParams::new(&target[q+1..]));
+            }
+        }
+
+        if let Some(host) = host {
+            self.headers.append(RequestHeader::Host, CowSlice::Ref(Slice::from_bytes(&buf[host])));
+        }
+        if let Some(cookie) = cookie {
+            self.headers.append(RequestHeader::Cookie, CowSlice::Ref(Slice::from_bytes(&buf[cookie])));
+        }
+        for (name, value) in fields {
+            let value = CowSlice::Ref(Slice::from_bytes(&buf[value]));
+            match RequestHeader::from_bytes(&buf[name.clone()]) {
+                Some(key) => self.headers.append(key, value),
+                None => self.headers.append_custom(Slice::from_bytes(&buf[name]), value),
+            }
+        }
+
+        if end_of_stream {return Ok(())}
+
+        let content_length = match head.headers.get(CONTENT_LENGTH) {
+            None => None,
+            Some(len) => Some(parse_content_length(len.as_bytes()).ok_or_else(Response::BadRequest)?),
+        };
+        if content_length.is_some_and(|len| len > config.max_payload_size) {
+            return Err(Response::PayloadTooLarge())
+        }
+
+        let Some(stream) = self.__stream__.as_mut() else {return Err(Response::InternalServerError())};
+        /* read later by `load_payload` or `BodyStream` */
+        let body = unsafe {
+            // SAFETY: `stream` is owned by `self` and not used by others until `connection` or `clear`
+            match content_length {
+                Some(0)   => return Ok(()),
+                Some(len) => body::Body::sized(stream, &[], len, 0, config),
+                None      => body::Body::until_eof(stream, config),
+            }
+        };
+        *self.__body__.get_mut().unwrap_or_else(std::sync::PoisonError::into_inner) = BodyState::Unread(body);
+
+        Ok(())
+    }
+
+    /// Read the body not yet read into `payload`
     #[cfg(feature="__rt_native__")]
-    #[inline]
-    async fn read_payload(
-        stream:        &mut (impl AsyncReader + Unpin),
-        remaining_buf: &[u8],
-        size:          usize,
-    ) -> CowSlice {
-        let remaining_buf_len = remaining_buf.len();
-
-        if remaining_buf_len == 0 || *unsafe {remaining_buf.get_unchecked(0)} == 0 {
-            #[cfg(feature="DEBUG")] println!("\n[read_payload] case: remaining_buf.is_empty() || remaining_buf[0] == 0\n");
-
-            let mut bytes = vec![0; size].into_boxed_slice();
-            stream.read_exact(&mut bytes).await.unwrap();
-            CowSlice::Own(bytes)
-
-        } else if size <= remaining_buf_len {
-            #[cfg(feature="DEBUG")] println!("\n[read_payload] case: starts_at + size <= BUF_SIZE\n");
-
-            #[allow(unused_unsafe/* I don't know why but rustc sometimes put warnings to this unsafe as unnecessary */)]
-            CowSlice::Ref(unsafe {
-                Slice::new_unchecked(remaining_buf.as_ptr(), size)
-            })
-
-        } else {
-            #[cfg(feature="DEBUG")] println!("\n[read_payload] case: else\n");
-
-            let mut bytes = vec![0; size].into_boxed_slice();
-            unsafe {// SAFETY: Here size > remaining_buf_len
-                bytes.get_unchecked_mut(..remaining_buf_len).copy_from_slice(remaining_buf);
-                stream.read_exact(bytes.get_unchecked_mut(remaining_buf_len..)).await.unwrap();
+    pub(crate) async fn load_payload(&mut self) -> Result<(), crate::Response> {
+
+        if let BodyState::Unread(body) = self.__body__.get_mut().unwrap_or_else(std::sync::PoisonError::into_inner) {
+            if body.is_read() {return Ok(())}
+
+            let payload = match body.read_all().await {
+                Err(res) => return Err(res.with_headers(|h| h.Connection("close"))),
+                Ok(payload) => payload
+            };
+            if !payload.is_empty() {
+                self.payload = Some(CowSlice::Own(payload.into_boxed_slice()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the body is left unread and not streamed
+    #[cfg(feature="__rt_native__")]
+    pub(crate) fn has_unread_payload(&mut self) -> bool {
+        matches!(
+            self.__body__.get_mut().unwrap_or_else(std::sync::PoisonError::into_inner),
+            BodyState::Unread(body) if !body.is_read()
+        )
+    }
+
+    /// Whether the client is waiting for `100 Continue` before sending the body
+    #[cfg(feature="__rt_native__")]
+    pub(crate) fn expects_continue(&mut self) -> bool {
+        matches!(
+            self.__body__.get_mut().unwrap_or_else(std::sync::PoisonError::into_inner),
+            BodyState::Unread(body) if body.expects_continue
+        )
+    }
+
+    /// Settle the body after handling this request,
+    /// keeping bytes read beyond it for the next request.
+    /// 
+    /// Returns `false` if the body is left unread or broken
+    /// and so the connection can't be reused.
+    #[cfg(feature="__rt_native__")]
+    pub(crate) fn end_payload(&mut self) -> bool {
+        let state = std::mem::replace(
+            self.__body__.get_mut().unwrap_or_else(std::sync::PoisonError::into_inner),
+            BodyState::None
+        );
+        match state {
+            BodyState::None      => true,
+            BodyState::Streaming => false,
+            BodyState::Unread(mut body) => {
+                if let Some(e) = body.error.take() {
+                    self.__io_error__ = Some(e);
+                }
+                let head_len = body.head_len;
+                match body.into_leftover() {
+                    None => false,
+                    /* the region after the head is free to hold the leftover */
+                    Some(leftover) if leftover.len() <= self.__buf__.len() - head_len => {
+                        self.__buf__[head_len..head_len + leftover.len()].copy_from_slice(&leftover);
+                        self.__leftover__ = head_len..(head_len + leftover.len());
+                        true
+                    }
+                    Some(_) => {
+                        crate::DEBUG!("[Request::end_payload] too large leftover after payload, closing connection");
+                        false
+                    }
+                }
             }
-            CowSlice::Own(bytes)
         }
     }
 
@@ -334,10 +732,7 @@ impl Request {
 
         let mut r = Reader::new(raw_bytes);
 
-        match Method::from_bytes(r.read_while(|b| b != &b' ')) {
-            None => return Ok(None),
-            Some(method) => self.method = method
-        }
+        self.method = parse_method(r.read_while(|b| b != &b' '))?;
 
         r.next_if(|b| *b==b' ').ok_or_else(Response::BadRequest)?;
         
@@ -369,7 +764,8 @@ impl Request {
         }
 
         let content_length = match self.headers.get_raw(RequestHeader::ContentLength) {
-            Some(v) => unsafe {v.as_bytes()}.into_iter().fold(0, |len, b| 10*len + (*b - b'0') as usize),
+            Some(v) => parse_content_length(unsafe {v.as_bytes()})
+                .ok_or_else(|| Response::BadRequest().with_headers(|h| h.Connection("close")))?,
             None    => 0,
         };
         self.payload = (content_length > 0).then(||
@@ -388,8 +784,8 @@ impl Request {
         self.env.write(env);
         self.ctx.write(ctx);
 
-        self.method = Method::from_worker(req.method())
-            .ok_or_else(|| Response::NotImplemented().with_text("ohkami doesn't support `CONNECT`, `TRACE` method"))?;
+        /* not `req.method()`, which reads any unknown method as `GET` */
+        self.method = parse_method(req.inner().method().as_bytes())?;
 
         self.__url__.write(req.url()
             .map_err(|_| Response::BadRequest().with_text("Invalid request URL"))?
@@ -432,6 +828,33 @@ impl Request {
         self.payload.as_deref()
     }
 
+    /// Authority ( `host[:port]` ) of the request target if it's in absolute-form,
+    /// like `example.com:8080` of `GET http://example.com:8080/path HTTP/1.1`,
+    /// typically sent to a forward proxy.
+    /// 
+    /// `None` for the usual origin-form `GET /path HTTP/1.1`; see `Host` header then.
+    #[cfg(feature="__rt_native__")]
+    #[inline]
+    pub fn authority(&self) -> Option<&str> {
+        std::str::from_utf8(unsafe {self.authority.as_ref()?.as_bytes()}).ok()
+    }
+
+    /// PROXY protocol header of the connection this request came from,
+    /// when `ServerConfig::proxy_protocol` is enabled
+    #[cfg(feature="__rt_native__")]
+    #[inline]
+    pub fn proxy(&self) -> Option<&crate::ProxyHeader> {
+        self.proxy.as_deref()
+    }
+
+    /// Which of the listeners `howl`s serve on this request arrived on,
+    /// `None` in `testing`
+    #[cfg(feature="__rt_native__")]
+    #[inline]
+    pub fn listener(&self) -> Option<&crate::ListenerInfo> {
+        self.listener.as_deref()
+    }
+
     /// Memorize any data within this request object
     #[inline] pub fn memorize<Value: Send + Sync + 'static>(&mut self, value: Value) {
         self.store.insert(value)
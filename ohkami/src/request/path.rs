@@ -2,45 +2,48 @@ use std::{borrow::Cow, mem::MaybeUninit};
 use ohkami_lib::{percent_decode_utf8, Slice};
 
 
-pub struct Path(
-    MaybeUninit<PathInner>
-);
-pub(crate) struct PathInner {
-    raw:    Slice,
+pub struct Path {
+    raw:    MaybeUninit<Slice>,
     params: Params,
 }
-struct Params {
-    next: usize,
-    list: [MaybeUninit<Slice>; Self::LIMIT]
-}
-impl Params {
-    const LIMIT: usize = 2;
-}
+/// Path params with their names in the route, in the order of appearance,
+/// as many as the route has. The buffer is kept across requests on a connection.
+struct Params(Vec<(&'static str, Slice)>);
 
 const _: () = {
     impl Params {
-        fn iter(&self) -> impl Iterator<Item = &Slice> {
-            (0..self.next).map(|i| unsafe {
-                self.list
-                    .get_unchecked(i)
-                    .assume_init_ref()
-            })
+        fn iter(&self) -> impl Iterator<Item = &(&'static str, Slice)> {
+            self.0.iter()
         }
     }
 
     impl Path {
         pub fn params(&self) -> impl Iterator<Item = Cow<str>> {
-            unsafe {self.0.assume_init_ref()}
-                .params.iter()
-                .map(|slice| percent_decode_utf8(unsafe {slice.as_bytes()})
+            self.params.iter()
+                .map(|(_, slice)| percent_decode_utf8(unsafe {slice.as_bytes()})
+                .expect("Non UTF-8 path params"))
+        }
+
+        /// Get the path param named `name` in the route ( `id` of `/users/:id` ),
+        /// decoded like `params`
+        pub fn param(&self, name: &str) -> Option<Cow<'_, str>> {
+            self.params.iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, slice)| percent_decode_utf8(unsafe {slice.as_bytes()})
                 .expect("Non UTF-8 path params"))
         }
 
+        /// Path params with their names, not decoded
+        pub(crate) fn raw_params(&self) -> impl Iterator<Item = (&'static str, &[u8])> {
+            self.params.iter()
+                .map(|(name, slice)| (*name, unsafe {slice.as_bytes()}))
+        }
+
         /// Get request path as `Cow::Borrowed(&str)` if it's not percent-encoded, or,
         /// decode it into `Cow::Owned(String)` if encoded in the original request.
         #[inline]
         pub fn str(&self) -> Cow<str> {
-            let bytes = unsafe {self.0.assume_init_ref().raw.as_bytes()};
+            let bytes = unsafe {self.raw.assume_init_ref().as_bytes()};
             if bytes.is_empty() {return Cow::Borrowed("/")}
             percent_decode_utf8(bytes).expect("Non UTF-8 path params")
         }
@@ -56,7 +59,7 @@ const _: () = {
     impl AsRef<str> for Path {
         #[inline]
         fn as_ref(&self) -> &str {
-            let bytes = &unsafe {self.0.assume_init_ref().raw.as_bytes()};
+            let bytes = &unsafe {self.raw.assume_init_ref().as_bytes()};
             if bytes.is_empty() {return "/"}
             std::str::from_utf8(bytes).expect("Non UTF-8 path params")
         }
@@ -79,26 +82,27 @@ const _: () = {
     impl Params {
         #[inline(always)]
         const fn init() -> Self {
-            Params { next: 0, list: [const {MaybeUninit::uninit()}; Params::LIMIT] }
+            Params(Vec::new())
         }
         
         #[inline(always)]
-        fn push(&mut self, param: Slice) {
-            #[cfg(debug_assertions)] {
-                assert!(self.next < Self::LIMIT);
-            }
-            unsafe {self.list
-                .get_unchecked_mut(self.next)
-                .write(param);
-            }
-            self.next += 1;
+        fn push(&mut self, name: &'static str, param: Slice) {
+            self.0.push((name, param))
         }
     }
     
     impl Path {
         #[inline]
-        pub(crate) fn uninit() -> Self {
-            Self(MaybeUninit::uninit())
+        pub(crate) const fn uninit() -> Self {
+            Self { raw: MaybeUninit::uninit(), params: Params::init() }
+        }
+
+        /// Back to `uninit` state, keeping the buffer for params
+        #[cfg(feature="__rt_native__")]
+        #[inline]
+        pub(crate) fn clear(&mut self) {
+            self.raw = MaybeUninit::uninit();
+            self.params.0.clear();
         }
 
         #[inline(always)]
@@ -119,34 +123,37 @@ const _: () = {
             if unsafe {*bytes.get_unchecked(len-1) == b'/'} {len -= 1};
 
             #[allow(unused_unsafe/* I don't know why but rustc sometimes put warnings to this unsafe as unnecessary */)]
-            Ok({self.0.write(PathInner {
-                raw:    unsafe {Slice::new_unchecked(bytes.as_ptr(), len)},
-                params: Params::init(),
-            });})
+            self.raw.write(unsafe {Slice::new_unchecked(bytes.as_ptr(), len)});
+            self.params.0.clear();
+            Ok(())
         }
 
-        #[inline] pub(crate) unsafe fn push_param(&mut self, param: Slice) {
-            self.0.assume_init_mut().params.push(param)
+        #[inline] pub(crate) unsafe fn push_param(&mut self, name: &'static str, param: Slice) {
+            self.params.push(name, param)
+        }
+        #[inline] pub(crate) fn n_params(&self) -> usize {
+            self.params.0.len()
         }
-        #[inline] pub(crate) unsafe fn assume_one_param<'p>(&self) -> &'p [u8] {
-            self.0.assume_init_ref().params.list.get_unchecked(0).assume_init_ref().as_bytes()
+        #[inline] pub(crate) fn truncate_params(&mut self, n: usize) {
+            self.params.0.truncate(n)
         }
-        #[inline] pub(crate) unsafe fn assume_two_params<'p>(&self) -> (&'p [u8], &'p [u8]) {
-            (self.0.assume_init_ref().params.list.get_unchecked(0).assume_init_ref().as_bytes(), self.0.assume_init_ref().params.list.get_unchecked(1).assume_init_ref().as_bytes())
+        /// `index`-th path param, `None` if the route has less params
+        #[inline] pub(crate) unsafe fn nth_param<'p>(&self, index: usize) -> Option<&'p [u8]> {
+            Some(self.params.0.get(index)?.1.as_bytes())
         }
 
         #[inline] pub(crate) unsafe fn normalized_bytes<'req>(&self) -> &'req [u8] {
-            self.0.assume_init_ref().raw.as_bytes()
+            self.raw.assume_init_ref().as_bytes()
         }
     }
     
     #[cfg(test)]
     impl Path {
         pub fn from_literal(literal: &'static str) -> Self {
-            Self(MaybeUninit::new(PathInner {
-                raw:    Slice::from_bytes(literal.as_bytes()),
+            Self {
+                raw:    MaybeUninit::new(Slice::from_bytes(literal.as_bytes())),
                 params: Params::init(),
-            }))
+            }
         }
     }
 };
@@ -7,7 +7,7 @@ macro_rules! assert_bytes_eq {
     ($res:expr, $expected:expr) => {
         {
             let mut res_bytes = Vec::new();
-            $res.send(&mut res_bytes).await;
+            $res.send(&mut res_bytes).await.unwrap();
 
             if res_bytes != $expected {
                 panic!("\n\
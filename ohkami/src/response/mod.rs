@@ -173,7 +173,7 @@ impl Response {
     #[cfg_attr(not(feature="sse"), inline)]
     pub(crate) async fn send(mut self,
         conn: &mut (impl AsyncWriter + Unpin)
-    ) -> Upgrade {
+    ) -> std::io::Result<Upgrade> {
         self.complete();
 
         match self.content {
@@ -185,10 +185,10 @@ impl Response {
                     crate::push_unchecked!(buf <- self.status.line());
                     self.headers.write_unchecked_to(&mut buf);
                 }
-                conn.write_all(&buf).await.expect("Failed to send response");
-                conn.flush().await.expect("Failed to flush connection");
+                conn.write_all(&buf).await?;
+                conn.flush().await?;
 
-                Upgrade::None
+                Ok(Upgrade::None)
             }
 
             Content::Payload(bytes) => {
@@ -201,14 +201,17 @@ impl Response {
                     self.headers.write_unchecked_to(&mut buf);
                     crate::push_unchecked!(buf <- bytes);
                 }
-                conn.write_all(&buf).await.expect("Failed to send response");
-                conn.flush().await.expect("Failed to flush connection");
+                conn.write_all(&buf).await?;
+                conn.flush().await?;
 
-                Upgrade::None
+                Ok(Upgrade::None)
             }
 
             #[cfg(feature="sse")]
             Content::Stream(mut stream) => {
+                /* not chunked for an HTTP/1.0 client, and then the end is told by closing the connection */
+                let chunked = self.headers.TransferEncoding().is_some();
+
                 let mut buf = Vec::<u8>::with_capacity(
                     self.status.line().len() +
                     self.headers.size
@@ -216,8 +219,8 @@ impl Response {
                     crate::push_unchecked!(buf <- self.status.line());
                     self.headers.write_unchecked_to(&mut buf);
                 }
-                conn.write_all(&buf).await.expect("Failed to send response");
-                conn.flush().await.expect("Failed to flush connection");
+                conn.write_all(&buf).await?;
+                conn.flush().await?;
 
                 while let Some(chunk) = stream.next().await {
                     match chunk {
@@ -225,17 +228,12 @@ impl Response {
                             crate::warning!("Error in stream: {msg}");
                             break
                         }
+                        Ok(chunk) if !chunked => {
+                            conn.write_all(&sse_data(&chunk)).await?;
+                            conn.flush().await?;
+                        }
                         Ok(chunk) => {
-                            let mut message = Vec::with_capacity(
-                                /* capacity for a single line */
-                                "data: ".len() + chunk.len() + "\n\n".len()
-                            );
-                            for line in chunk.split('\n') {
-                                message.extend_from_slice(b"data: ");
-                                message.extend_from_slice(line.as_bytes());
-                                message.push(b'\n');
-                            }
-                            message.push(b'\n');
+                            let mut message = sse_data(&chunk);
 
                             let size_hex_bytes = ohkami_lib::num::hexized_bytes(message.len());
 
@@ -247,15 +245,17 @@ impl Response {
                             #[cfg(feature="DEBUG")]
                             println!("\n[sending chunk]\n{}", chunk.escape_ascii());
 
-                            conn.write_all(&chunk).await.expect("Failed to send response");
-                            conn.flush().await.expect("Failed to flush connection");
+                            conn.write_all(&chunk).await?;
+                            conn.flush().await?;
                         }
                     }
                 }
-                conn.write_all(b"0\r\n\r\n").await.expect("Failed to send response");
-                conn.flush().await.expect("Failed to flush connection");
+                if chunked {
+                    conn.write_all(b"0\r\n\r\n").await?;
+                    conn.flush().await?;
+                }
 
-                Upgrade::None
+                Ok(Upgrade::None)
             }
 
             #[cfg(all(feature="ws", feature="__rt_native__"))]
@@ -267,15 +267,31 @@ impl Response {
                     crate::push_unchecked!(buf <- self.status.line());
                     self.headers.write_unchecked_to(&mut buf);
                 }
-                conn.write_all(&buf).await.expect("Failed to send response");
-                conn.flush().await.expect("Failed to flush connection");
+                conn.write_all(&buf).await?;
+                conn.flush().await?;
 
-                Upgrade::WebSocket((config, handler))
+                Ok(Upgrade::WebSocket((config, handler)))
             }
         }
     }
 }
 
+#[cfg(all(feature="sse", feature="__rt_native__"))]
+/// Format a chunk of `Content::Stream` as a Server-Sent Events message
+pub(crate) fn sse_data(chunk: &str) -> Vec<u8> {
+    let mut message = Vec::with_capacity(
+        /* capacity for a single line */
+        "data: ".len() + chunk.len() + "\n\n".len()
+    );
+    for line in chunk.split('\n') {
+        message.extend_from_slice(b"data: ");
+        message.extend_from_slice(line.as_bytes());
+        message.push(b'\n');
+    }
+    message.push(b'\n');
+    message
+}
+
 impl Response {
     #[inline]
     pub fn with_headers(mut self, h: impl FnOnce(SetHeaders)->SetHeaders) -> Self {
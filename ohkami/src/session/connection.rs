@@ -0,0 +1,280 @@
+use std::{io, pin::Pin, task::{Context, Poll}};
+use crate::__rt__::TcpStream;
+
+/* `futures_rustls` for all the runtimes, with tokio's `TcpStream` adapted to `futures_io` */
+#[cfg(all(feature="tls", feature="rt_tokio"))]
+type TlsStream = ::futures_rustls::server::TlsStream<TokioIo>;
+#[cfg(all(feature="tls", any(feature="rt_async-std", feature="rt_smol", feature="rt_glommio")))]
+type TlsStream = ::futures_rustls::server::TlsStream<TcpStream>;
+
+
+/// Underlying stream of a session
+pub enum Connection {
+    Tcp(TcpStream),
+
+    #[cfg(unix)]
+    Unix(crate::__rt__::UnixStream),
+
+    #[cfg(feature="tls")]
+    Tls(Box<TlsStream>),
+}
+
+impl crate::request::Incoming for Connection {
+    fn interim(&mut self) -> Option<&mut (dyn crate::__rt__::AsyncWrite + Unpin)> {
+        Some(self)
+    }
+
+    fn connection(&mut self) -> Option<&mut Connection> {
+        Some(self)
+    }
+    #[cfg(feature="ws")]
+    fn into_connection(self: Box<Self>) -> Option<Connection> {
+        Some(*self)
+    }
+}
+
+#[cfg(feature="tls")]
+impl Connection {
+    /// Perform TLS handshake on a `Tcp` connection.
+    ///
+    /// `self` is returned as it is if it's already `Tls`.
+    pub(crate) async fn accept_tls(self, tls: &crate::ohkami::TlsConfig) -> io::Result<Self> {
+        let Self::Tcp(tcp) = self else {return Ok(self)};
+
+        #[cfg(feature="rt_tokio")]
+        let tcp = TokioIo(tcp);
+
+        ::futures_rustls::TlsAcceptor::from(tls.0.clone())
+            .accept(tcp).await.map(|tls| Self::Tls(Box::new(tls)))
+    }
+}
+
+/// tokio's `TcpStream` as `futures_io::{AsyncRead, AsyncWrite}` for `futures_rustls`
+#[cfg(all(feature="tls", feature="rt_tokio"))]
+pub struct TokioIo(TcpStream);
+#[cfg(all(feature="tls", feature="rt_tokio"))]
+const _: () = {
+    use ::tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+    impl ::futures_io::AsyncRead for TokioIo {
+        #[inline]
+        fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+            let mut buf = ReadBuf::new(buf);
+            std::task::ready!(Pin::new(&mut self.0).poll_read(cx, &mut buf))?;
+            Poll::Ready(Ok(buf.filled().len()))
+        }
+    }
+
+    impl ::futures_io::AsyncWrite for TokioIo {
+        #[inline]
+        fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+            Pin::new(&mut self.0).poll_write(cx, buf)
+        }
+
+        #[inline]
+        fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.0).poll_flush(cx)
+        }
+
+        #[inline]
+        fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.0).poll_shutdown(cx)
+        }
+    }
+};
+
+/// HTTP/2 connection preface sent by the client ( RFC 9113, 3.4 )
+#[cfg(feature="http2")]
+const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+/// Protocol a client is going to speak on a connection,
+/// with the bytes already read from the connection to tell it
+#[cfg(feature="http2")]
+pub(crate) enum Protocol {
+    Http1(Vec<u8>),
+    Http2(&'static [u8]),
+}
+
+#[cfg(feature="http2")]
+impl Connection {
+    /// `Http2` if negotiated `h2` via ALPN, or started with the connection preface
+    /// ( h2c prior knowledge ), otherwise `Http1`.
+    /// 
+    /// For a plain TCP connection, this reads the first bytes,
+    /// only up to the length of the preface and as long as they match it.
+    pub(crate) async fn protocol(&mut self) -> io::Result<Protocol> {
+        use crate::__rt__::AsyncReader;
+
+        match self {
+            Self::Tcp(_) => {
+                let mut buf = vec![0; PREFACE.len()];
+                let mut n = 0;
+                while n < PREFACE.len() {
+                    match self.read(&mut buf[n..]).await? {
+                        0 => break,
+                        m => n += m,
+                    }
+                    if buf[..n] != PREFACE[..n] {break}
+                }
+                buf.truncate(n);
+                Ok(if buf == PREFACE {Protocol::Http2(PREFACE)} else {Protocol::Http1(buf)})
+            }
+
+            /* h2c over Unix domain socket is not supported */
+            #[cfg(unix)]
+            Self::Unix(_) => Ok(Protocol::Http1(Vec::new())),
+
+            #[cfg(feature="tls")]
+            Self::Tls(tls) => Ok(if tls.get_ref().1.alpn_protocol() == Some(b"h2") {
+                Protocol::Http2(&[])
+            } else {
+                Protocol::Http1(Vec::new())
+            }),
+        }
+    }
+}
+
+#[cfg(feature="rt_tokio")]
+const _: () = {
+    use ::tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+    #[cfg(feature="tls")]
+    use ::futures_io::{AsyncRead as FuturesAsyncRead, AsyncWrite as FuturesAsyncWrite};
+
+    impl AsyncRead for Connection {
+        #[inline]
+        fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+            match self.get_mut() {
+                Self::Tcp(tcp) => Pin::new(tcp).poll_read(cx, buf),
+                #[cfg(unix)]
+                Self::Unix(unix) => Pin::new(unix).poll_read(cx, buf),
+                #[cfg(feature="tls")]
+                Self::Tls(tls) => {
+                    let n = std::task::ready!(FuturesAsyncRead::poll_read(Pin::new(&mut **tls), cx, buf.initialize_unfilled()))?;
+                    buf.advance(n);
+                    Poll::Ready(Ok(()))
+                }
+            }
+        }
+    }
+
+    impl AsyncWrite for Connection {
+        #[inline]
+        fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+            match self.get_mut() {
+                Self::Tcp(tcp) => Pin::new(tcp).poll_write(cx, buf),
+                #[cfg(unix)]
+                Self::Unix(unix) => Pin::new(unix).poll_write(cx, buf),
+                #[cfg(feature="tls")]
+                Self::Tls(tls) => FuturesAsyncWrite::poll_write(Pin::new(&mut **tls), cx, buf),
+            }
+        }
+
+        #[inline]
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            match self.get_mut() {
+                Self::Tcp(tcp) => Pin::new(tcp).poll_flush(cx),
+                #[cfg(unix)]
+                Self::Unix(unix) => Pin::new(unix).poll_flush(cx),
+                #[cfg(feature="tls")]
+                Self::Tls(tls) => FuturesAsyncWrite::poll_flush(Pin::new(&mut **tls), cx),
+            }
+        }
+
+        #[inline]
+        fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            match self.get_mut() {
+                Self::Tcp(tcp) => Pin::new(tcp).poll_shutdown(cx),
+                #[cfg(unix)]
+                Self::Unix(unix) => Pin::new(unix).poll_shutdown(cx),
+                #[cfg(feature="tls")]
+                Self::Tls(tls) => FuturesAsyncWrite::poll_close(Pin::new(&mut **tls), cx),
+            }
+        }
+    }
+};
+
+#[cfg(any(feature="rt_async-std", feature="rt_smol", feature="rt_glommio"))]
+const _: () = {
+    use ::futures_util::io::{AsyncRead, AsyncWrite};
+
+    impl AsyncRead for Connection {
+        #[inline]
+        fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+            match self.get_mut() {
+                Self::Tcp(tcp) => Pin::new(tcp).poll_read(cx, buf),
+                #[cfg(unix)]
+                Self::Unix(unix) => Pin::new(unix).poll_read(cx, buf),
+                #[cfg(feature="tls")]
+                Self::Tls(tls) => Pin::new(&mut **tls).poll_read(cx, buf),
+            }
+        }
+    }
+
+    impl AsyncWrite for Connection {
+        #[inline]
+        fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+            match self.get_mut() {
+                Self::Tcp(tcp) => Pin::new(tcp).poll_write(cx, buf),
+                #[cfg(unix)]
+                Self::Unix(unix) => Pin::new(unix).poll_write(cx, buf),
+                #[cfg(feature="tls")]
+                Self::Tls(tls) => Pin::new(&mut **tls).poll_write(cx, buf),
+            }
+        }
+
+        #[inline]
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            match self.get_mut() {
+                Self::Tcp(tcp) => Pin::new(tcp).poll_flush(cx),
+                #[cfg(unix)]
+                Self::Unix(unix) => Pin::new(unix).poll_flush(cx),
+                #[cfg(feature="tls")]
+                Self::Tls(tls) => Pin::new(&mut **tls).poll_flush(cx),
+            }
+        }
+
+        #[inline]
+        fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            match self.get_mut() {
+                Self::Tcp(tcp) => Pin::new(tcp).poll_close(cx),
+                #[cfg(unix)]
+                Self::Unix(unix) => Pin::new(unix).poll_close(cx),
+                #[cfg(feature="tls")]
+                Self::Tls(tls) => Pin::new(&mut **tls).poll_close(cx),
+            }
+        }
+    }
+};
+
+/// `h2` requires tokio's I/O traits
+#[cfg(all(feature="http2", any(feature="rt_async-std", feature="rt_smol", feature="rt_glommio")))]
+const _: () = {
+    use ::tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+    use ::futures_util::io::{AsyncRead as FuturesAsyncRead, AsyncWrite as FuturesAsyncWrite};
+
+    impl AsyncRead for Connection {
+        #[inline]
+        fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+            let n = std::task::ready!(FuturesAsyncRead::poll_read(self, cx, buf.initialize_unfilled()))?;
+            buf.advance(n);
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    impl AsyncWrite for Connection {
+        #[inline]
+        fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+            FuturesAsyncWrite::poll_write(self, cx, buf)
+        }
+
+        #[inline]
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            FuturesAsyncWrite::poll_flush(self, cx)
+        }
+
+        #[inline]
+        fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            FuturesAsyncWrite::poll_close(self, cx)
+        }
+    }
+};
@@ -0,0 +1,324 @@
+use std::{pin::Pin, sync::Arc, task::{Context, Poll}, time::Duration};
+use ::bytes::Bytes;
+use ::h2::{RecvStream, SendStream, server::SendResponse};
+use ohkami_lib::signal::watch;
+use super::{Connection, ConnectionInfo, panicking, overloaded};
+use crate::response::Content;
+use crate::util::timeout_in;
+use crate::ohkami::{router::RadixRouter, limit::Limit, panic::catch_unwinding, ServerConfig, ConnectionPhase};
+use crate::util::until;
+use crate::{__rt__, Response};
+
+
+/// Serve an HTTP/2 connection, handling each stream as a task
+pub(super) async fn serve(
+    connection: Connection,
+    read:       &'static [u8],
+    router:     Arc<RadixRouter>,
+    config:     Arc<ServerConfig>,
+    info:       ConnectionInfo,
+    in_flight:  Option<Arc<Limit>>,
+) {
+    let mut h2 = match timeout_in(config.header_read_timeout,
+        ::h2::server::Builder::new()
+            .max_header_list_size(config.max_header_size.try_into().unwrap_or(u32::MAX))
+            .handshake(Rewind { read, connection })
+    ).await {
+        Some(Ok(h2)) => h2,
+        Some(Err(e)) => {
+            config.report(ConnectionPhase::Handshake, info.ip, io_error(e));
+            return
+        }
+        None => {
+            crate::DEBUG!("HTTP/2 handshake timed out");
+            return
+        }
+    };
+
+    /* `keepalive_timeout` is for idle time between streams, so it runs only while no stream is open */
+    let (open_streams, idle) = watch::channel(0_usize);
+    let open_streams = Arc::new(open_streams);
+    while let Some(Some(Some(stream))) = until(info.shutdown.notified(),
+        until(idle_for(config.keepalive_timeout, idle.clone()), h2.accept())
+    ).await {
+        match stream {
+            Ok((req, respond)) => {
+                let open = OpenStream::new(&open_streams);
+                let handle = handle(req, respond, router.clone(), config.clone(), info.clone(), in_flight.clone());
+                let stream = async move {handle.await; drop(open)};
+
+                #[cfg(any(feature="rt_tokio", feature="rt_async-std"))]
+                __rt__::spawn(stream);
+                #[cfg(any(feature="rt_smol", feature="rt_glommio"))]
+                __rt__::spawn(stream).detach();
+            }
+            Err(e) => {
+                config.report(ConnectionPhase::Read, info.ip, io_error(e));
+                break
+            }
+        }
+    }
+
+    crate::DEBUG!("about to shutdown HTTP/2 connection");
+
+    h2.graceful_shutdown();
+    let _ = std::future::poll_fn(|cx| h2.poll_closed(cx)).await;
+}
+
+/// Done when no stream has been open for `timeout`
+async fn idle_for(timeout: Duration, mut open_streams: watch::Receiver<usize>) {
+    loop {
+        let _ = open_streams.wait_for(|n| *n == 0).await;
+        if timeout_in(timeout, open_streams.wait_for(|n| *n > 0)).await.is_none() {
+            return
+        }
+    }
+}
+
+/// Counted in `open_streams` while alive
+struct OpenStream(Arc<watch::Sender<usize>>);
+impl OpenStream {
+    fn new(open_streams: &Arc<watch::Sender<usize>>) -> Self {
+        open_streams.send_modify(|n| *n += 1);
+        Self(open_streams.clone())
+    }
+}
+impl Drop for OpenStream {
+    fn drop(&mut self) {
+        self.0.send_modify(|n| *n -= 1);
+    }
+}
+
+/// Connection with the bytes already read from it ( the preface ) put back
+struct Rewind {
+    read:       &'static [u8],
+    connection: Connection,
+}
+const _: () = {
+    use ::tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+    impl AsyncRead for Rewind {
+        fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+            let this = self.get_mut();
+            if this.read.is_empty() {
+                return Pin::new(&mut this.connection).poll_read(cx, buf)
+            }
+            let n = buf.remaining().min(this.read.len());
+            buf.put_slice(&this.read[..n]);
+            this.read = &this.read[n..];
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    impl AsyncWrite for Rewind {
+        fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+            Pin::new(&mut self.connection).poll_write(cx, buf)
+        }
+        fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Pin::new(&mut self.connection).poll_flush(cx)
+        }
+        fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Pin::new(&mut self.connection).poll_shutdown(cx)
+        }
+    }
+};
+
+async fn handle(
+    req:         ::http::Request<RecvStream>,
+    mut respond: SendResponse<Bytes>,
+    router:      Arc<RadixRouter>,
+    config:      Arc<ServerConfig>,
+    info:        ConnectionInfo,
+    in_flight:   Option<Arc<Limit>>,
+) {
+    let permit = in_flight.as_ref().map(Limit::try_acquire);
+    let res = 'res: {
+        if matches!(permit, Some(None)) {
+            break 'res overloaded(config.retry_after)
+        }
+        if let Err(res) = expect_continue(&req, &mut respond, &config) {
+            break 'res res
+        }
+
+        let (head, body) = req.into_parts();
+        let end_of_stream = body.is_end_stream();
+
+        let mut request = info.request().with_stream(RequestBody { recv: body, data: Bytes::new() });
+        let mut request = unsafe {Pin::new_unchecked(&mut request)};
+        match request.as_mut().read_h2(head, end_of_stream, &config) {
+            Ok(()) => match catch_unwinding(router.handle(request.as_mut().get_mut())).await {
+                Ok(res)    => res,
+                Err(panic) => panicking(panic),
+            },
+            Err(res) => res,
+        }
+    };
+    drop(permit);
+
+    if let Err(e) = send(res, &mut respond).await {
+        config.report(ConnectionPhase::Write, info.ip, io_error(e));
+    }
+}
+
+/// Request body on an HTTP/2 stream, releasing the flow-control capacity
+/// for each DATA frame received
+struct RequestBody {
+    recv: RecvStream,
+    /// rest of the last DATA frame
+    data: Bytes,
+}
+
+impl crate::request::Incoming for RequestBody {}
+
+impl RequestBody {
+    fn poll_read_data(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<std::io::Result<usize>> {
+        while self.data.is_empty() {
+            match std::task::ready!(self.recv.poll_data(cx)) {
+                None => return Poll::Ready(Ok(0)),
+                Some(Err(e)) => return Poll::Ready(Err(io_error(e))),
+                Some(Ok(data)) => {
+                    let _ = self.recv.flow_control().release_capacity(data.len());
+                    self.data = data;
+                }
+            }
+        }
+        let n = buf.len().min(self.data.len());
+        buf[..n].copy_from_slice(&self.data.split_to(n));
+        Poll::Ready(Ok(n))
+    }
+}
+
+#[cfg(feature="rt_tokio")]
+impl crate::__rt__::AsyncRead for RequestBody {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ::tokio::io::ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        let n = std::task::ready!(self.get_mut().poll_read_data(cx, buf.initialize_unfilled()))?;
+        buf.advance(n);
+        Poll::Ready(Ok(()))
+    }
+}
+#[cfg(any(feature="rt_async-std", feature="rt_smol", feature="rt_glommio"))]
+impl crate::__rt__::AsyncRead for RequestBody {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<std::io::Result<usize>> {
+        self.get_mut().poll_read_data(cx, buf)
+    }
+}
+
+fn io_error(e: ::h2::Error) -> std::io::Error {
+    if e.is_io() {
+        e.into_io().unwrap_or_else(|| std::io::ErrorKind::Other.into())
+    } else {
+        std::io::Error::other(e)
+    }
+}
+
+/// Send `100 Continue` if the client expects it and the declared length is acceptable.
+/// 
+/// Unlike HTTP/1.1, this is sent before routing,
+/// so fangs can't reject the body before `100 Continue`.
+fn expect_continue(
+    req:     &::http::Request<RecvStream>,
+    respond: &mut SendResponse<Bytes>,
+    config:  &ServerConfig,
+) -> Result<(), Response> {
+    let Some(expect) = req.headers().get(::http::header::EXPECT) else {return Ok(())};
+
+    if !expect.as_bytes().eq_ignore_ascii_case(b"100-continue") {
+        return Err(Response::ExpectationFailed())
+    }
+    if req.headers().get(::http::header::CONTENT_LENGTH)
+        .and_then(|len| len.to_str().ok()?.parse::<usize>().ok())
+        .is_some_and(|len| len > config.max_payload_size)
+    {
+        return Err(Response::PayloadTooLarge())
+    }
+
+    let mut interim = ::http::Response::new(());
+    *interim.status_mut() = ::http::StatusCode::CONTINUE;
+    respond.send_informational(interim).map_err(|_| Response::InternalServerError())
+}
+
+async fn send(mut res: Response, respond: &mut SendResponse<Bytes>) -> Result<(), ::h2::Error> {
+    res.complete();
+
+    let mut head = ::http::Response::builder()
+        .status(res.status.code());
+    for (name, value) in res.headers.iter() {
+        /* connection-specific headers are prohibited in HTTP/2 */
+        if [
+            "Connection", "Keep-Alive", "Proxy-Connection", "Transfer-Encoding", "Upgrade"
+        ].iter().any(|h| h.eq_ignore_ascii_case(name)) {continue}
+
+        head = head.header(name, value);
+    }
+    let head = head.body(()).unwrap_or_else(|_| {
+        let mut head = ::http::Response::new(());
+        *head.status_mut() = ::http::StatusCode::INTERNAL_SERVER_ERROR;
+        head
+    });
+
+    match res.content {
+        Content::None => {
+            respond.send_response(head, true)?;
+        }
+
+        Content::Payload(bytes) => {
+            let mut stream = respond.send_response(head, false)?;
+            send_data(&mut stream, Bytes::from(
+                unsafe {bytes.into_cow_static_bytes_uncheked()}.into_owned()
+            ), true).await?;
+        }
+
+        #[cfg(feature="sse")]
+        Content::Stream(mut chunks) => {
+            use crate::util::StreamExt;
+
+            let mut stream = respond.send_response(head, false)?;
+            while let Some(chunk) = chunks.next().await {
+                match chunk {
+                    Err(msg) => {
+                        crate::warning!("Error in stream: {msg}");
+                        break
+                    }
+                    Ok(chunk) => {
+                        send_data(&mut stream, Bytes::from(crate::response::sse_data(&chunk)), false).await?;
+                    }
+                }
+            }
+            send_data(&mut stream, Bytes::new(), true).await?;
+        }
+
+        #[cfg(feature="ws")]
+        Content::WebSocket(_) => {
+            crate::warning!("WebSocket over HTTP/2 is not supported");
+            let mut head = head;
+            *head.status_mut() = ::http::StatusCode::NOT_IMPLEMENTED;
+            respond.send_response(head, true)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Send `data` as DATA frames, each within the capacity granted by
+/// the peer's flow-control window, waiting for the window to open
+async fn send_data(
+    stream:        &mut SendStream<Bytes>,
+    mut data:      Bytes,
+    end_of_stream: bool,
+) -> Result<(), ::h2::Error> {
+    if data.is_empty() {
+        return stream.send_data(data, end_of_stream)
+    }
+    while !data.is_empty() {
+        stream.reserve_capacity(data.len());
+        let capacity = match std::future::poll_fn(|cx| stream.poll_capacity(cx)).await {
+            Some(capacity) => capacity?,
+            None => return Err(::h2::Reason::STREAM_CLOSED.into()),
+        };
+        if capacity == 0 {continue}
+
+        let frame = data.split_to(capacity.min(data.len()));
+        stream.send_data(frame, end_of_stream && data.is_empty())?;
+    }
+    Ok(())
+}
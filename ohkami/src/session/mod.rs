@@ -1,114 +1,231 @@
 #![cfg(feature="__rt_native__")]
 
-use std::{any::Any, pin::Pin, sync::Arc, time::Duration};
-use std::panic::{AssertUnwindSafe, catch_unwind};
-use crate::__rt__::TcpStream;
-use crate::response::Upgrade;
-use crate::util::timeout_in;
-use crate::ohkami::router::RadixRouter;
-use crate::{Request, Response};
+mod connection;
+pub(crate) use connection::Connection;
+
+#[cfg(feature="http2")]
+mod http2;
 
+mod proxy;
+pub use proxy::{ProxyHeader, ProxyTls};
 
-mod env {
-    #![allow(unused, non_snake_case)]
+use std::{any::Any, pin::Pin, sync::Arc};
+use crate::response::Upgrade;
+use crate::ohkami::{router::RadixRouter, limit::Limit, panic::catch_unwinding, ServerConfig, ConnectionPhase};
+use crate::request::has_token;
+use crate::{Request, Response, Shutdown, ListenerInfo};
 
-    use std::sync::OnceLock;
 
-    pub(crate) fn OHKAMI_KEEPALIVE_TIMEOUT() -> u64 {
-        static OHKAMI_KEEPALIVE_TIMEOUT: OnceLock<u64> = OnceLock::new();
-        *OHKAMI_KEEPALIVE_TIMEOUT.get_or_init(|| {
-            std::env::var("OHKAMI_KEEPALIVE_TIMEOUT").ok()
-                .map(|v| v.parse().ok()).flatten()
-                .unwrap_or(42)
-        })
-    }
+#[cold] #[inline(never)]
+fn panicking(panic: Box<dyn Any + Send>) -> Response {
+    crate::warning!("[Panicked]: {}", crate::PanicInfo::new(panic));
+    crate::Response::InternalServerError()
+}
+
+/// `503 Service Unavailable` beyond `ServerConfig::max_requests_in_flight`
+#[cold] #[inline(never)]
+fn overloaded(retry_after: std::time::Duration) -> Response {
+    let secs = retry_after.as_secs() + (retry_after.subsec_nanos() > 0) as u64;
+    crate::Response::ServiceUnavailable().with_headers(|h| h
+        .RetryAfter(secs.to_string())
+    )
+}
 
-    #[cfg(feature="ws")]
-    pub(crate) fn OHKAMI_WEBSOCKET_TIMEOUT() -> u64 {
-        static OHKAMI_WEBSOCKET_TIMEOUT: OnceLock<u64> = OnceLock::new();
-        *OHKAMI_WEBSOCKET_TIMEOUT.get_or_init(|| {
-            std::env::var("OHKAMI_WEBSOCKET_TIMEOUT").ok()
-                .map(|v| v.parse().ok()).flatten()
-                .unwrap_or(1 * 60 * 60)
-        })
+/// What all the requests on a connection share
+#[derive(Clone)]
+struct ConnectionInfo {
+    ip:       Option<std::net::IpAddr>,
+    proxy:    Option<Arc<ProxyHeader>>,
+    listener: Arc<ListenerInfo>,
+    shutdown: Shutdown,
+}
+impl ConnectionInfo {
+    fn request(&self) -> Request {
+        let mut req = Request::init(self.ip);
+        req.shutdown = Some(self.shutdown.clone());
+        req.proxy    = self.proxy.clone();
+        req.listener = Some(self.listener.clone());
+        req
     }
 }
 
 pub(crate) struct Session {
     router:     Arc<RadixRouter>,
-    connection: TcpStream,
-    ip:         std::net::IpAddr,
+    config:     Arc<ServerConfig>,
+    connection: Connection,
+    info:       ConnectionInfo,
+    in_flight:  Option<Arc<Limit>>,
 }
 impl Session {
     pub(crate) fn new(
         router:     Arc<RadixRouter>,
-        connection: TcpStream,
-        ip:         std::net::IpAddr
+        config:     Arc<ServerConfig>,
+        connection: Connection,
+        ip:         Option<std::net::IpAddr>,
+        listener:   Arc<ListenerInfo>,
+        shutdown:   Shutdown,
+        in_flight:  Option<Arc<Limit>>,
     ) -> Self {
         Self {
             router,
+            config,
             connection,
-            ip
+            info: ConnectionInfo { ip, proxy: None, listener, shutdown },
+            in_flight,
         }
     }
 
     pub(crate) async fn manage(mut self) {
-        #[cold] #[inline(never)]
-        fn panicking(panic: Box<dyn Any + Send>) -> Response {
-            if let Some(msg) = panic.downcast_ref::<String>() {
-                crate::warning!("[Panicked]: {msg}");
-            } else if let Some(msg) = panic.downcast_ref::<&str>() {
-                crate::warning!("[Panicked]: {msg}");
-            } else {
-                crate::warning!("[Panicked]");
+        /* the PROXY protocol header comes before everything including TLS handshake */
+        if self.config.proxy_protocol {
+            match crate::util::timeout_in(self.config.header_read_timeout,
+                proxy::read_header(&mut self.connection)
+            ).await {
+                Some(Ok(header)) => {
+                    if let Some(source) = header.source {
+                        self.info.ip = Some(source.ip());
+                    }
+                    self.info.proxy = Some(Arc::new(header));
+                }
+                Some(Err(e)) => return self.config.report(ConnectionPhase::Handshake, self.info.ip, e),
+                None => {
+                    crate::DEBUG!("PROXY protocol header timed out");
+                    return
+                }
+            }
+        }
+
+        #[cfg(feature="tls")]
+        if let Some(tls) = self.config.tls.clone() {
+            match crate::util::timeout_in(self.config.header_read_timeout,
+                self.connection.accept_tls(&tls)
+            ).await {
+                Some(Ok(connection)) => self.connection = connection,
+                Some(Err(e)) => {
+                    self.config.report(ConnectionPhase::Handshake, self.info.ip, e);
+                    return
+                }
+                None => {
+                    crate::DEBUG!("TLS handshake timed out");
+                    return
+                }
             }
-            crate::Response::InternalServerError()
         }
 
-        match timeout_in(Duration::from_secs(env::OHKAMI_KEEPALIVE_TIMEOUT()), async {
-            let mut req = Request::init(self.ip);
+        #[cfg(feature="http2")]
+        let read = match crate::util::timeout_in(self.config.keepalive_timeout, self.connection.protocol()).await {
+            Some(Ok(connection::Protocol::Http2(read))) => return http2::serve(self.connection, read, self.router, self.config, self.info, self.in_flight).await,
+            Some(Ok(connection::Protocol::Http1(read))) => read,
+            Some(Err(e)) => return self.config.report(ConnectionPhase::Read, self.info.ip, e),
+            None => return,
+        };
+
+        let mut req = self.info.request().with_stream(self.connection);
+        #[cfg(feature="http2")] {
+            /* the first bytes of the first request, read to tell the protocol */
+            req = req.with_preread(&read);
+        }
+        let upgrade = {
             let mut req = unsafe {Pin::new_unchecked(&mut req)};
             loop {
                 req.clear();
-                match req.as_mut().read(&mut self.connection).await {
+                /* on shutdown, an idle connection ( or one in the middle of a request head ) is just closed */
+                let Some(read) = crate::util::until(self.info.shutdown.notified(),
+                    req.as_mut().read(&self.config)
+                ).await else {
+                    crate::DEBUG!("closing connection for graceful shutdown");
+                    break Upgrade::None
+                };
+                let upgrade = match read {
                     Ok(Some(())) => {
-                        let close = matches!(req.headers.Connection(), Some("close" | "Close"));
-
-                        let res = match catch_unwind(AssertUnwindSafe({
-                            let req = req.as_mut();
-                            || self.router.handle(req.get_mut())
-                        })) {
-                            Ok(future) => future.await,
-                            Err(panic) => panicking(panic),
+                        let keep_alive = req.headers.Connection().is_some_and(|c| has_token(c, "keep-alive"));
+                        /* HTTP/1.0 closes the connection after a response unless `Connection: keep-alive` */
+                        let close = if req.http10 {!keep_alive} else {
+                            req.headers.Connection().is_some_and(|c| has_token(c, "close"))
+                        };
+                        /* an intermediary may have framed a request with both `Transfer-Encoding` and `Content-Length` otherwise */
+                        let close = close || req.length_conflict;
+
+                        let permit = self.in_flight.as_ref().map(Limit::try_acquire);
+                        let mut res = if matches!(permit, Some(None)) {
+                            overloaded(self.config.retry_after)
+                        } else {
+                            match catch_unwinding(self.router.handle(req.as_mut().get_mut())).await {
+                                Ok(res)    => res,
+                                Err(panic) => panicking(panic),
+                            }
                         };
-                        let upgrade = res.send(&mut self.connection).await;
+                        drop(permit);
+
+                        /* an HTTP/1.0 client, not knowing chunked encoding, reads a stream until the connection is closed */
+                        #[cfg(feature="sse")]
+                        let close = close || (req.http10 && matches!(res.content, crate::response::Content::Stream(_)) && {
+                            res.headers.set().TransferEncoding(None);
+                            true
+                        });
+
+                        /* the rest of the body, if any, can't be told from the next request */
+                        let close = close || !req.as_mut().get_mut().end_payload() || self.info.shutdown.is_notified();
+                        if close {
+                            res.headers.set().Connection("close");
+                        } else if keep_alive {
+                            /* an HTTP/1.0 client needs this to keep the connection */
+                            res.headers.set().Connection("keep-alive");
+                        }
 
-                        if !upgrade.is_none() {break upgrade}
-                        if close {break Upgrade::None}
+                        if let Some(e) = req.as_mut().get_mut().__io_error__.take() {
+                            self.config.report(ConnectionPhase::Read, self.info.ip, e);
+                        }
+                        let Some(connection) = req.as_mut().get_mut().connection() else {break Upgrade::None};
+                        match res.send(connection).await {
+                            Ok(upgrade) if !upgrade.is_none() => Some(upgrade),
+                            Ok(_) => close.then_some(Upgrade::None),
+                            Err(e) => {
+                                self.config.report(ConnectionPhase::Write, self.info.ip, e);
+                                Some(Upgrade::None)
+                            }
+                        }
                     }
-                    Ok(None) => break Upgrade::None,
-                    Err(res) => {res.send(&mut self.connection).await;},
-                }
+                    Ok(None) => {
+                        if let Some(e) = req.as_mut().get_mut().__io_error__.take() {
+                            self.config.report(ConnectionPhase::Read, self.info.ip, e);
+                        }
+                        Some(Upgrade::None)
+                    }
+                    Err(mut res) => {
+                        /* where the next request starts is unknown after a rejected one */
+                        res.headers.set().Connection("close");
+                        let Some(connection) = req.as_mut().get_mut().connection() else {break Upgrade::None};
+                        if let Err(e) = res.send(connection).await {
+                            self.config.report(ConnectionPhase::Write, self.info.ip, e);
+                        }
+                        Some(Upgrade::None)
+                    }
+                };
+                if let Some(upgrade) = upgrade {break upgrade}
             }
-        }).await {
-            Some(Upgrade::None) | None => {
+        };
+
+        match upgrade {
+            Upgrade::None => {
                 crate::DEBUG!("about to shutdown connection");
             }
 
             #[cfg(feature="ws")]
-            Some(Upgrade::WebSocket((config, handler))) => {
+            Upgrade::WebSocket((config, handler)) => {
                 use crate::ws::{Connection, Message, CloseFrame, CloseCode};
 
                 crate::DEBUG!("WebSocket session started");
 
-                let mut conn = Connection::new(self.connection, config);
+                let Some(connection) = req.take_connection() else {return};
+                let mut conn = Connection::new(connection, config);
 
-                let close = timeout_in(Duration::from_secs(env::OHKAMI_WEBSOCKET_TIMEOUT()),
+                let close = crate::util::timeout_in(self.config.websocket_timeout,
                     handler(conn.clone())
                 ).await;
 
                 if !conn.is_closed() {
-                    conn.send(Message::Close(Some(match close {
+                    let closed = conn.send(Message::Close(Some(match close {
                         Some(_) => {
                             crate::DEBUG!("Closing WebSocket session...");
                             CloseFrame {
@@ -117,13 +234,16 @@ impl Session {
                             }
                         }
                         None => {
-                            crate::warning!("[WARNING] WebSocket session is aborted by `OHKAMI_WEBSOCKET_TIMEOUT` (default to 1 hour, and can be set via environment variable)");
+                            crate::warning!("[WARNING] WebSocket session is aborted by `websocket_timeout` (default to 1 hour, and can be set via `ServerConfig`)");
                             CloseFrame {
                                 code:   CloseCode::Library(4000),
-                                reason: Some("OHKAMI_WEBSOCKET_TIMEOUT".into())
+                                reason: Some("websocket_timeout".into())
                             }
                         }
-                    }))).await.expect("Failed to send close message");
+                    }))).await;
+                    if let Err(e) = closed {
+                        self.config.report(ConnectionPhase::WebSocket, self.info.ip, e);
+                    }
                 }
 
                 crate::DEBUG!("WebSocket session finished");
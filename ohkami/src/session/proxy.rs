@@ -0,0 +1,296 @@
+use std::io::{self, ErrorKind};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use crate::__rt__::{AsyncRead, AsyncReader};
+
+
+/// # PROXY protocol header
+///
+/// Sent by a proxy or load balancer in front of Ohkami ( e.g. HAProxy, AWS NLB )
+/// at the beginning of a connection when `ServerConfig::proxy_protocol` is enabled.
+///
+/// Then `Request::ip` is the original client's address taken from this,
+/// and this is available by `Request::proxy`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProxyHeader {
+    /// Original client's address, `None` for `UNKNOWN` ( v1 ), `LOCAL` or non-IP ( v2 )
+    pub source:      Option<SocketAddr>,
+    /// Address the client connected to
+    pub destination: Option<SocketAddr>,
+    /// Host name the client sent by TLS SNI or HTTP ( `PP2_TYPE_AUTHORITY` in v2 )
+    pub authority:   Option<String>,
+    /// TLS terminated by the proxy ( `PP2_TYPE_SSL` in v2 )
+    pub tls:         Option<ProxyTls>,
+}
+
+/// TLS connection between the client and the proxy, by PROXY protocol v2
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProxyTls {
+    /// like `TLSv1.3`
+    pub version:       Option<String>,
+    /// like `ECDHE-RSA-AES128-GCM-SHA256`
+    pub cipher:        Option<String>,
+    /// Common name of the client certificate
+    pub client_cn:     Option<String>,
+    /// Whether the client presented a certificate and it was verified
+    pub client_verified: bool,
+}
+
+const V1_PREFIX:    &[u8] = b"PROXY ";
+const V1_MAX_LEN:   usize = 107;
+const V2_SIGNATURE: &[u8; 12] = b"\r\n\r\n\0\r\nQUIT\n";
+
+/// Read a PROXY protocol v1 or v2 header at the beginning of `conn`,
+/// not reading any byte after it
+pub(super) async fn read_header(conn: &mut (impl AsyncRead + Unpin)) -> io::Result<ProxyHeader> {
+    /* the shortest header is `PROXY UNKNOWN\r\n` */
+    let mut buf = vec![0; 15];
+    conn.read_exact(&mut buf).await?;
+
+    if buf.starts_with(V1_PREFIX) {
+        while !buf.ends_with(b"\r\n") {
+            if buf.len() == V1_MAX_LEN {
+                return Err(invalid("PROXY v1 header is too long"))
+            }
+            let mut b = [0];
+            conn.read_exact(&mut b).await?;
+            buf.push(b[0]);
+        }
+        parse_v1(&buf)
+
+    } else if buf.starts_with(&V2_SIGNATURE[..]) {
+        let mut b = [0];
+        conn.read_exact(&mut b).await?;
+        buf.push(b[0]);
+
+        let len = u16::from_be_bytes([buf[14], buf[15]]) as usize;
+        let mut rest = vec![0; len];
+        conn.read_exact(&mut rest).await?;
+        parse_v2([buf[12], buf[13]], &rest)
+
+    } else {
+        Err(invalid("missing PROXY protocol header"))
+    }
+}
+
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, msg)
+}
+
+fn parse_v1(line: &[u8]) -> io::Result<ProxyHeader> {
+    let line = std::str::from_utf8(&line[V1_PREFIX.len()..line.len() - 2])
+        .map_err(|_| invalid("PROXY v1 header is not ASCII"))?;
+
+    let mut fields = line.split(' ');
+    let (source, destination) = match fields.next() {
+        Some("UNKNOWN") => (None, None),
+        Some(proto @ ("TCP4" | "TCP6")) => {
+            let mut next = || fields.next().ok_or_else(|| invalid("too few fields in PROXY v1 header"));
+            let (src, dst, src_port, dst_port) = (next()?, next()?, next()?, next()?);
+            if fields.next().is_some() {
+                return Err(invalid("too many fields in PROXY v1 header"))
+            }
+
+            let ip = |ip: &str| match proto {
+                "TCP4" => ip.parse::<Ipv4Addr>().map(IpAddr::V4).ok(),
+                _      => ip.parse::<Ipv6Addr>().map(IpAddr::V6).ok(),
+            }.ok_or_else(|| invalid("invalid address in PROXY v1 header"));
+            let port = |port: &str| port.parse::<u16>().ok()
+                .filter(|_| !port.starts_with('0') || port == "0")
+                .ok_or_else(|| invalid("invalid port in PROXY v1 header"));
+
+            (
+                Some(SocketAddr::new(ip(src)?, port(src_port)?)),
+                Some(SocketAddr::new(ip(dst)?, port(dst_port)?)),
+            )
+        }
+        _ => return Err(invalid("unknown protocol in PROXY v1 header"))
+    };
+
+    Ok(ProxyHeader { source, destination, authority: None, tls: None })
+}
+
+fn parse_v2([ver_cmd, family]: [u8; 2], rest: &[u8]) -> io::Result<ProxyHeader> {
+    if ver_cmd >> 4 != 2 {
+        return Err(invalid("unsupported PROXY protocol version"))
+    }
+    let local = match ver_cmd & 0x0F {
+        0x0 => true,
+        0x1 => false,
+        _   => return Err(invalid("unknown command in PROXY v2 header"))
+    };
+
+    let (addresses, tlvs) = match family {
+        /* TCP over IPv4 */
+        0x11 => {
+            let (a, tlvs) = split_at_checked(rest, 12)?;
+            let ip = |i: usize| IpAddr::V4(Ipv4Addr::new(a[i], a[i+1], a[i+2], a[i+3]));
+            let port = |i: usize| u16::from_be_bytes([a[i], a[i+1]]);
+            (Some((
+                SocketAddr::new(ip(0), port(8)),
+                SocketAddr::new(ip(4), port(10)),
+            )), tlvs)
+        }
+        /* TCP over IPv6 */
+        0x21 => {
+            let (a, tlvs) = split_at_checked(rest, 36)?;
+            let ip = |i: usize| IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(&a[i..i+16]).unwrap()));
+            let port = |i: usize| u16::from_be_bytes([a[i], a[i+1]]);
+            (Some((
+                SocketAddr::new(ip(0), port(32)),
+                SocketAddr::new(ip(16), port(34)),
+            )), tlvs)
+        }
+        /* Unix stream socket */
+        0x31 => (None, split_at_checked(rest, 216)?.1),
+        /* unspecified, or datagram that HTTP doesn't use */
+        _ => (None, &[][..]),
+    };
+
+    let mut header = ProxyHeader {
+        source:      None,
+        destination: None,
+        authority:   None,
+        tls:         None,
+    };
+    if !local {
+        if let Some((source, destination)) = addresses {
+            header.source      = Some(source);
+            header.destination = Some(destination);
+        }
+    }
+
+    for (r#type, value) in tlvs_of(tlvs)? {
+        match r#type {
+            /* PP2_TYPE_AUTHORITY */
+            0x02 => header.authority = Some(utf8(value)?),
+            /* PP2_TYPE_SSL */
+            0x20 => {
+                let (head, sub_tlvs) = split_at_checked(value, 5)?;
+                let client = head[0];
+                let verify = u32::from_be_bytes([head[1], head[2], head[3], head[4]]);
+
+                /* PP2_CLIENT_SSL */
+                if client & 0x01 == 0 {continue}
+
+                let mut tls = ProxyTls {
+                    version:   None,
+                    cipher:    None,
+                    client_cn: None,
+                    /* PP2_CLIENT_CERT_CONN or PP2_CLIENT_CERT_SESS, and verified */
+                    client_verified: client & 0x06 != 0 && verify == 0,
+                };
+                for (r#type, value) in tlvs_of(sub_tlvs)? {
+                    match r#type {
+                        0x21 => tls.version   = Some(utf8(value)?),
+                        0x22 => tls.client_cn = Some(utf8(value)?),
+                        0x23 => tls.cipher    = Some(utf8(value)?),
+                        _ => ()
+                    }
+                }
+                header.tls = Some(tls);
+            }
+            _ => ()
+        }
+    }
+
+    Ok(header)
+}
+
+fn split_at_checked(bytes: &[u8], mid: usize) -> io::Result<(&[u8], &[u8])> {
+    bytes.split_at_checked(mid).ok_or_else(|| invalid("PROXY v2 header is too short"))
+}
+
+fn utf8(value: &[u8]) -> io::Result<String> {
+    String::from_utf8(value.to_vec()).map_err(|_| invalid("non UTF-8 value in PROXY v2 header"))
+}
+
+fn tlvs_of(mut bytes: &[u8]) -> io::Result<Vec<(u8, &[u8])>> {
+    let mut tlvs = Vec::new();
+    while !bytes.is_empty() {
+        let (head, rest) = split_at_checked(bytes, 3)?;
+        let len = u16::from_be_bytes([head[1], head[2]]) as usize;
+        let (value, rest) = split_at_checked(rest, len)?;
+        tlvs.push((head[0], value));
+        bytes = rest;
+    }
+    Ok(tlvs)
+}
+
+
+#[cfg(all(test, any(feature="rt_tokio", feature="rt_async-std")))]
+mod test {
+    use super::*;
+
+    async fn read(bytes: &[u8]) -> io::Result<(ProxyHeader, Vec<u8>)> {
+        let mut conn = bytes;
+        let header = read_header(&mut conn).await?;
+        Ok((header, conn.to_vec()))
+    }
+
+    #[crate::__rt__::test]
+    async fn test_proxy_v1() {
+        let (header, rest) = read(b"PROXY TCP4 192.0.2.1 198.51.100.1 56324 443\r\nGET / HTTP/1.1\r\n\r\n").await.unwrap();
+        assert_eq!(header.source, Some("192.0.2.1:56324".parse().unwrap()));
+        assert_eq!(header.destination, Some("198.51.100.1:443".parse().unwrap()));
+        assert_eq!(rest, b"GET / HTTP/1.1\r\n\r\n");
+
+        let (header, _) = read(b"PROXY TCP6 2001:db8::1 2001:db8::2 4000 80\r\n").await.unwrap();
+        assert_eq!(header.source, Some("[2001:db8::1]:4000".parse().unwrap()));
+
+        let (header, rest) = read(b"PROXY UNKNOWN\r\nGET").await.unwrap();
+        assert_eq!(header.source, None);
+        assert_eq!(rest, b"GET");
+
+        assert!(read(b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n").await.is_err());
+        assert!(read(b"PROXY TCP4 192.0.2.1 198.51.100.1 56324\r\n").await.is_err());
+        assert!(read(b"PROXY TCP4 2001:db8::1 198.51.100.1 56324 443\r\n").await.is_err());
+        assert!(read(b"PROXY TCP4 192.0.2.1 198.51.100.1 056324 443\r\n").await.is_err());
+        assert!(read(&[b"PROXY UNKNOWN".as_slice(), &[b' '; 100], b"\r\n"].concat()).await.is_err());
+    }
+
+    #[crate::__rt__::test]
+    async fn test_proxy_v2() {
+        let mut bytes = V2_SIGNATURE.to_vec();
+        bytes.extend([0x21, 0x11]);
+
+        let mut rest = vec![192, 0, 2, 1, 198, 51, 100, 1];
+        rest.extend(56324u16.to_be_bytes());
+        rest.extend(443u16.to_be_bytes());
+        /* PP2_TYPE_AUTHORITY */
+        rest.extend([0x02, 0, 11]);
+        rest.extend(b"example.com");
+        /* PP2_TYPE_SSL with PP2_SUBTYPE_SSL_VERSION */
+        rest.extend([0x20, 0, 5 + 3 + 7, 0x01, 0, 0, 0, 0, 0x21, 0, 7]);
+        rest.extend(b"TLSv1.3");
+
+        bytes.extend((rest.len() as u16).to_be_bytes());
+        bytes.extend(rest);
+        bytes.extend(b"GET / HTTP/1.1\r\n\r\n");
+
+        let (header, rest) = read(&bytes).await.unwrap();
+        assert_eq!(header, ProxyHeader {
+            source:      Some("192.0.2.1:56324".parse().unwrap()),
+            destination: Some("198.51.100.1:443".parse().unwrap()),
+            authority:   Some("example.com".into()),
+            tls:         Some(ProxyTls {
+                version:   Some("TLSv1.3".into()),
+                cipher:    None,
+                client_cn: None,
+                client_verified: false,
+            }),
+        });
+        assert_eq!(rest, b"GET / HTTP/1.1\r\n\r\n");
+
+        /* LOCAL ( e.g. health check by the proxy itself ) */
+        let mut bytes = V2_SIGNATURE.to_vec();
+        bytes.extend([0x20, 0x00, 0, 0]);
+        let (header, _) = read(&bytes).await.unwrap();
+        assert_eq!(header.source, None);
+
+        /* truncated TLV */
+        let mut bytes = V2_SIGNATURE.to_vec();
+        bytes.extend([0x21, 0x11, 0, 15]);
+        bytes.extend([192, 0, 2, 1, 198, 51, 100, 1, 0, 80, 0, 80, 0x02, 0, 5]);
+        assert!(read(&bytes).await.is_err());
+    }
+}
@@ -55,10 +55,17 @@ impl TestingOhkami {
         let router = self.0.clone();
         
         let res = async move {
-            let mut request = Request::init(#[cfg(feature="__rt_native__")] crate::util::IP_0000);
+            #[cfg(feature="__rt_native__")]
+            let mut request = Request::init(Some(crate::util::IP_0000))
+                .with_stream(crate::request::InMemory::new(req.encode()));
+            #[cfg(feature="rt_worker")]
+            let mut request = Request::init();
             let mut request = unsafe {Pin::new_unchecked(&mut request)};
             
-            let res = match request.as_mut().read(&mut &req.encode()[..]).await {
+            let res = match request.as_mut().read(
+                #[cfg(feature="rt_worker")] &mut &req.encode()[..],
+                #[cfg(feature="__rt_native__")] &crate::ServerConfig::default(),
+            ).await {
                 Ok(Some(())) => router.handle(&mut request).await,
                 Ok(None) => panic!("No request"),
                 Err(res) => res,
@@ -131,6 +138,18 @@ macro_rules! new_test_request {
 } new_test_request! {
     GET PUT POST PATCH DELETE HEAD OPTIONS
 }
+impl TestRequest {
+    /// Request of `method` given by name, typically an extension method like `PROPFIND`
+    pub fn method(method: &str, path: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            method:  Method::from_bytes(method.as_bytes()).unwrap_or_else(|| panic!("Invalid method `{method}`")),
+            path:    path.into(),
+            queries: HashMap::new(),
+            headers: HashMap::new(),
+            content: None,
+        }
+    }
+}
 
 impl TestRequest {
     pub fn query(mut self, key: impl Into<Cow<'static, str>>, value: impl Into<Cow<'static, str>>) -> Self {
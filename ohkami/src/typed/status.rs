@@ -50,7 +50,7 @@ macro_rules! generate_statuses_as_types_containing_value {
     URITooLong                    : "414 URI Too Long",
     UnsupportedMediaType          : "415 Unsupported Media Type",
     RangeNotSatisfiable           : "416 Range Not Satisfiable",
-    ExceptionFailed               : "417 Exception Failed",
+    ExpectationFailed             : "417 Expectation Failed",
     Im_a_teapot                   : "418 I'm a teapot",
     MisdirectedRequest            : "421 Misdirected Request",
     UnprocessableEntity           : "422 Unprocessable Entity",
@@ -75,6 +75,9 @@ macro_rules! generate_statuses_as_types_containing_value {
     NetworkAuthenticationRequired : "511 Network Authentication Required",
 }
 
+#[deprecated = "misspelled, use `ExpectationFailed`"]
+pub type ExceptionFailed<B = ()> = ExpectationFailed<B>;
+
 macro_rules! generate_statuses_as_types_with_no_value {
     ($( $status:ident : $message:literal, )*) => {
         $(
@@ -37,7 +37,7 @@ macro_rules! DEBUG {
 
 pub use crate::fang::FangAction;
 
-#[cfg(feature="sse")]
+#[cfg(any(feature="sse", feature="__rt_native__"))]
 pub use ohkami_lib::stream::{self, Stream, StreamExt};
 
 #[cfg(not(feature="rt_worker"))]
@@ -119,6 +119,25 @@ pub fn timeout_in<T>(
     Timeout { proc, sleep: crate::__rt__::sleep(duration) }
 }
 
+#[cfg(feature="__rt_native__")]
+/// `None` if `signal` is done before `proc`
+pub(crate) fn until<T>(
+    signal: impl std::future::Future<Output = ()>,
+    proc:   impl std::future::Future<Output = T>
+) -> impl std::future::Future<Output = Option<T>> {
+    use std::task::Poll;
+
+    async {
+        let (mut signal, mut proc) = (std::pin::pin!(signal), std::pin::pin!(proc));
+        std::future::poll_fn(|cx| {
+            if signal.as_mut().poll(cx).is_ready() {
+                return Poll::Ready(None)
+            }
+            proc.as_mut().poll(cx).map(Some)
+        }).await
+    }
+}
+
 #[cfg(feature="__rt_native__")]
 pub const IP_0000: std::net::IpAddr = std::net::IpAddr::V4(std::net::Ipv4Addr::new(0, 0, 0, 0));
 
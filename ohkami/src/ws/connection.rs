@@ -5,7 +5,7 @@ use crate::__rt__::{AsyncWriter, AsyncReader};
 
 
 /// WebSocket connection
-pub struct Connection<Conn: AsyncWriter + AsyncReader + Unpin> {
+pub struct Connection<Conn: AsyncWriter + AsyncReader + Unpin = crate::session::Connection> {
     conn:       Arc<UnsafeCell<(State, Conn)>>,
     config:     Config,
     n_buffered: usize,
@@ -89,7 +89,7 @@ pub(super) async fn write(
     *n_buffered += n;
     if *n_buffered > config.write_buffer_size {
         if *n_buffered > config.max_write_buffer_size {
-            panic!("Buffered messages is larger than `max_write_buffer_size`");
+            return Err(Error::other("Buffered messages is larger than `max_write_buffer_size`"))
         } else {
             flush(conn, n_buffered).await?
         }
@@ -149,15 +149,16 @@ impl<Conn: AsyncWriter + AsyncReader + Unpin> Connection<Conn> {
 #[cfg(feature="rt_tokio")]
 pub mod split {
     use super::*;
-    use tokio::net::{TcpStream, tcp::{ReadHalf as TcpReadHalf, WriteHalf as TcpWriteHalf}};
+    use crate::session::Connection as SessionConnection;
+    use tokio::io::{ReadHalf as IoReadHalf, WriteHalf as IoWriteHalf};
 
 
-    impl Connection<TcpStream> {
+    impl Connection<SessionConnection> {
         pub fn split(self) -> (ReadHalf, WriteHalf) {
             let (state, conn) = unsafe {&mut *self.conn.get()};
             state.assert_alive();
     
-            let (read, write) = conn.split();
+            let (read, write) = tokio::io::split(conn);
             (
                 ReadHalf  { conn: read,  config: self.config.clone() },
                 WriteHalf { conn: write, config: self.config, n_buffered: self.n_buffered }
@@ -166,7 +167,7 @@ pub mod split {
     }
 
     pub struct ReadHalf {
-        conn:   TcpReadHalf<'static>,
+        conn:   IoReadHalf<&'static mut SessionConnection>,
         config: Config,
     }
     impl ReadHalf {
@@ -179,7 +180,7 @@ pub mod split {
     }
 
     pub struct WriteHalf {
-        conn:       TcpWriteHalf<'static>,
+        conn:       IoWriteHalf<&'static mut SessionConnection>,
         config:     Config,
         n_buffered: usize,
     }
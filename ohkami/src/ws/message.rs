@@ -157,14 +157,20 @@ impl Message {
 
             OpCode::Close => {
                 let payload = first_frame.payload;
-                Ok(Some(Message::Close(
-                    (! payload.is_empty()).then(|| {
-                        let (code_bytes, rem) = payload.split_at(2);
-                        let code   = CloseCode::from_bytes(unsafe {(code_bytes.as_ptr() as *const [u8; 2]).read()});
-                        let reason = (! rem.is_empty()).then(|| Cow::Owned(String::from_utf8(rem.to_vec()).unwrap()));
-                        CloseFrame { code, reason }
-                    })
-                )))
+                if payload.is_empty() {
+                    return Ok(Some(Message::Close(None)))
+                }
+                if payload.len() < 2 {
+                    return Err(Error::new(ErrorKind::InvalidData, "Incoming close payload is too short"))
+                }
+                let (code_bytes, rem) = payload.split_at(2);
+                let code   = CloseCode::from_bytes([code_bytes[0], code_bytes[1]]);
+                let reason = match rem {
+                    [] => None,
+                    _  => Some(Cow::Owned(String::from_utf8(rem.to_vec())
+                        .map_err(|_| Error::new(ErrorKind::InvalidData, "Incoming close reason is not UTF-8"))?))
+                };
+                Ok(Some(Message::Close(Some(CloseFrame { code, reason }))))
             }
 
             OpCode::Continue => Err(Error::new(ErrorKind::InvalidData, "Unexpected continue frame"))
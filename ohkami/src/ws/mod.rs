@@ -10,7 +10,7 @@ pub use connection::Connection;
 #[cfg(feature="rt_tokio")] pub use connection::split;
 
 use std::{future::Future, pin::Pin};
-use crate::{__rt__, FromRequest, IntoResponse, Request, Response};
+use crate::{FromRequest, IntoResponse, Request, Response};
 
 
 /// # Context for WebSocket handshake
@@ -54,14 +54,14 @@ pub struct WebSocketContext<'req> {
 
     impl<'ctx> WebSocketContext<'ctx> {
         pub fn connect<Fut: Future<Output = ()> + Send + 'static>(self,
-            handler: impl FnOnce(Connection<__rt__::TcpStream>) -> Fut + Send + Sync + 'static
+            handler: impl FnOnce(Connection) -> Fut + Send + Sync + 'static
         ) -> WebSocket {
             self.connect_with(Config::default(), handler)
         }
 
         pub fn connect_with<Fut: Future<Output = ()> + Send + 'static>(self,
             config:  Config,
-            handler: impl FnOnce(Connection<__rt__::TcpStream>) -> Fut + Send + Sync + 'static
+            handler: impl FnOnce(Connection) -> Fut + Send + Sync + 'static
         ) -> WebSocket {
             WebSocket {
                 config,
@@ -76,7 +76,7 @@ pub struct WebSocketContext<'req> {
 };
 
 pub(crate) type Handler = Box<dyn
-    FnOnce(Connection<__rt__::TcpStream>) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+    FnOnce(Connection) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
     + Send + Sync
 >;
 